@@ -3,9 +3,11 @@ use eframe::egui;
 use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
 use egui_material_icons as icons;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::BufWriter;
+use std::path::PathBuf;
 
 // Notes Canvas Data Structures
 #[derive(Clone, Serialize, Deserialize)]
@@ -49,11 +51,273 @@ impl Default for NotesCanvas {
 }
 
 // Todo App Data Structures
+
+const APP_NAME: &str = "Todo App"; // Also names the release storage directory
+
+// Bump when the saved format changes, and add a step to `TodoApp::migrate`
+const SCHEMA_VERSION: u32 = 1;
+
+// Fixed font sizes for the todo view
+const HEADING_SIZE: f32 = 24.0;
+const PROJECT_TITLE_SIZE: f32 = 20.0; // Larger font for project titles
+const LABEL_SIZE: f32 = 16.0;
+const BUTTON_SIZE: f32 = 14.0;
+const TEXT_SIZE: f32 = 16.0; // Increased task text size for better visibility
+
+// UI language
+#[derive(Clone, Copy, Default, PartialEq, Hash, Serialize, Deserialize)]
+enum Lang {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Lang {
+    const ALL: [Lang; 2] = [Lang::English, Lang::Spanish];
+
+    /// The language's own name, as shown in the selector.
+    fn name(self) -> &'static str {
+        match self {
+            Lang::English => "English",
+            Lang::Spanish => "Español",
+        }
+    }
+}
+
+// Translatable UI strings, looked up with `t`
+#[derive(Clone, Copy)]
+enum Tr {
+    AppTitle,
+    User,
+    NewProject,
+    AddProject,
+    Settings,
+    Trash,
+    Stats,
+    ExportCsv,
+    ExportJson,
+    Import,
+    QuickTask,
+    TaskTextHint,
+    CreateProjectFirst,
+    Add,
+    Search,
+    FilterTasksHint,
+    FuzzyHint,
+    Fuzzy,
+    StatusAll,
+    StatusActive,
+    StatusDone,
+    OverdueOnly,
+    FlaggedOnly,
+    ShowSnoozed,
+    ProjectsView,
+    DueToday,
+    DueThisWeek,
+    SmartOrder,
+    RightClickHint, // "{icons}" is replaced by the expand/collapse icons
+    NewTask,
+    AddTaskHint,
+    Language,
+    AccentColor,
+    ProjectsAndTasks,
+    Feedback,
+    DangerZone,
+    CompletedCount, // "{count}" is replaced by the number of completed tasks
+    Today,
+    Tomorrow,
+    OverdueDate, // "{date}" is replaced by the formatted due date
+    NothingDueToday,
+    NothingDueThisWeek,
+    NoTasksYet,
+    NoTasksYetHint,
+}
+
+/// `key` in `lang`.
+fn t(lang: Lang, key: Tr) -> &'static str {
+    match lang {
+        Lang::English => match key {
+            Tr::AppTitle => "Todo App",
+            Tr::User => "User",
+            Tr::NewProject => "New Project:",
+            Tr::AddProject => "Add Project",
+            Tr::Settings => "Settings",
+            Tr::Trash => "Trash",
+            Tr::Stats => "Stats",
+            Tr::ExportCsv => "Export CSV",
+            Tr::ExportJson => "Export JSON",
+            Tr::Import => "Import",
+            Tr::QuickTask => "Quick Task:",
+            Tr::TaskTextHint => "Task text",
+            Tr::CreateProjectFirst => "Create a project first",
+            Tr::Add => "Add",
+            Tr::Search => "Search:",
+            Tr::FilterTasksHint => "Filter tasks",
+            Tr::FuzzyHint => "Fuzzy match, e.g. \"bymlk\"",
+            Tr::Fuzzy => "Fuzzy",
+            Tr::StatusAll => "All tasks",
+            Tr::StatusActive => "Active only",
+            Tr::StatusDone => "Done only",
+            Tr::OverdueOnly => "Overdue only",
+            Tr::FlaggedOnly => "Flagged only",
+            Tr::ShowSnoozed => "Show snoozed",
+            Tr::ProjectsView => "Projects",
+            Tr::DueToday => "Due today",
+            Tr::DueThisWeek => "Due this week",
+            Tr::SmartOrder => "Smart order",
+            Tr::RightClickHint => {
+                "Right-click on the expand/collapse button ({icons}) to add tasks directly!"
+            }
+            Tr::NewTask => "New Task:",
+            Tr::AddTaskHint => "Add a task...",
+            Tr::Language => "Language",
+            Tr::AccentColor => "Accent color",
+            Tr::ProjectsAndTasks => "Projects & tasks",
+            Tr::Feedback => "Feedback",
+            Tr::DangerZone => "Danger zone",
+            Tr::CompletedCount => "Completed ({count})",
+            Tr::Today => "Today",
+            Tr::Tomorrow => "Tomorrow",
+            Tr::OverdueDate => "{date} (overdue)",
+            Tr::NothingDueToday => "Nothing due today",
+            Tr::NothingDueThisWeek => "Nothing due this week",
+            Tr::NoTasksYet => "No tasks yet",
+            Tr::NoTasksYetHint => {
+                "No tasks yet — right-click the arrow or press the add button to create one"
+            }
+        },
+        Lang::Spanish => match key {
+            Tr::AppTitle => "Lista de tareas",
+            Tr::User => "Usuario",
+            Tr::NewProject => "Nuevo proyecto:",
+            Tr::AddProject => "Añadir proyecto",
+            Tr::Settings => "Ajustes",
+            Tr::Trash => "Papelera",
+            Tr::Stats => "Estadísticas",
+            Tr::ExportCsv => "Exportar CSV",
+            Tr::ExportJson => "Exportar JSON",
+            Tr::Import => "Importar",
+            Tr::QuickTask => "Tarea rápida:",
+            Tr::TaskTextHint => "Texto de la tarea",
+            Tr::CreateProjectFirst => "Crea primero un proyecto",
+            Tr::Add => "Añadir",
+            Tr::Search => "Buscar:",
+            Tr::FilterTasksHint => "Filtrar tareas",
+            Tr::FuzzyHint => "Búsqueda aproximada, p. ej. \"bymlk\"",
+            Tr::Fuzzy => "Aproximada",
+            Tr::StatusAll => "Todas las tareas",
+            Tr::StatusActive => "Solo pendientes",
+            Tr::StatusDone => "Solo completadas",
+            Tr::OverdueOnly => "Solo vencidas",
+            Tr::FlaggedOnly => "Solo destacadas",
+            Tr::ShowSnoozed => "Mostrar pospuestas",
+            Tr::ProjectsView => "Proyectos",
+            Tr::DueToday => "Para hoy",
+            Tr::DueThisWeek => "Para esta semana",
+            Tr::SmartOrder => "Orden inteligente",
+            Tr::RightClickHint => {
+                "¡Haz clic derecho en el botón de expandir/contraer ({icons}) para añadir tareas directamente!"
+            }
+            Tr::NewTask => "Nueva tarea:",
+            Tr::AddTaskHint => "Añadir una tarea...",
+            Tr::Language => "Idioma",
+            Tr::AccentColor => "Color de acento",
+            Tr::ProjectsAndTasks => "Proyectos y tareas",
+            Tr::Feedback => "Respuesta visual",
+            Tr::DangerZone => "Zona de peligro",
+            Tr::CompletedCount => "Completadas ({count})",
+            Tr::Today => "Hoy",
+            Tr::Tomorrow => "Mañana",
+            Tr::OverdueDate => "{date} (vencida)",
+            Tr::NothingDueToday => "Nada para hoy",
+            Tr::NothingDueThisWeek => "Nada para esta semana",
+            Tr::NoTasksYet => "Aún no hay tareas",
+            Tr::NoTasksYetHint => {
+                "Aún no hay tareas — haz clic derecho en la flecha o pulsa el botón de añadir para crear una"
+            }
+        },
+    }
+}
+
+// Every keyboard shortcut as (keys, action), listed in the F1 overlay.
+// Keep in sync with the input handling in `update` and `handle_project_shortcuts`.
+const SHORTCUTS: [(&str, &str); 10] = [
+    ("Ctrl+S", "Save now"),
+    ("Ctrl+Z", "Undo the last delete or bulk change"),
+    ("Up / Down", "Move between projects"),
+    ("Enter / Ctrl+E", "Expand or collapse the focused project"),
+    ("Ctrl+1 ... Ctrl+9", "Jump to the Nth project"),
+    ("N", "Add a task to the focused project"),
+    ("Delete", "Delete the selected task"),
+    ("Enter", "Confirm the field being edited"),
+    ("Escape", "Cancel the field being edited"),
+    ("F1 / ?", "Show this list"),
+];
+
+// Deferred UI actions, applied after the project list has been drawn
+type ProjectAction = (&'static str, usize, String); // (action, project_id, text)
+type TaskAction = (&'static str, usize, usize, String); // (action, project_id, task_id, text)
+
 #[derive(Clone, Serialize, Deserialize)]
 struct Task {
     id: usize,
     text: String,
     completed: bool,
+    #[serde(default)]
+    created_at: Option<DateTime<Local>>, // None for tasks from older saves
+    #[serde(default)]
+    completed_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    due_date: Option<NaiveDate>,
+    #[serde(default)]
+    due_time: Option<NaiveTime>, // Only meaningful together with a due date
+    #[serde(default)]
+    flagged: bool, // Starred as important; independent of completion
+    #[serde(default)]
+    snoozed: bool, // Due date was pushed back with "Snooze"
+    #[serde(default)]
+    blocked_by: Option<usize>, // ID of a task in the same project that must be done first
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    expanded: bool, // Notes section open; persisted like `Project::expanded`
+    #[serde(default)]
+    estimate_minutes: Option<u32>, // Rough time the task should take
+}
+
+impl Task {
+    fn set_completed(&mut self, completed: bool) {
+        self.completed = completed;
+        self.completed_at = completed.then(Local::now);
+    }
+
+    /// The moment a reminder should fire, if the task has both a due date and time.
+    fn due_at(&self) -> Option<NaiveDateTime> {
+        Some(self.due_date?.and_time(self.due_time?))
+    }
+
+    /// Whole days an open task has existed, if it has a creation time.
+    fn open_age_days(&self, now: DateTime<Local>) -> Option<i64> {
+        let created_at = self.created_at.filter(|_| !self.completed)?;
+        Some(now.signed_duration_since(created_at).num_days())
+    }
+
+    /// Snoozed to a date that hasn't come yet.
+    fn is_snoozed(&self, today: NaiveDate) -> bool {
+        self.snoozed && !self.completed && self.due_date.is_some_and(|date| date > today)
+    }
+
+    /// Open and past due. A date without a time is overdue from the next day.
+    fn is_overdue(&self, now: NaiveDateTime) -> bool {
+        if self.completed {
+            return false;
+        }
+        match (self.due_date, self.due_at()) {
+            (_, Some(due)) => due <= now,
+            (Some(date), None) => date < now.date(),
+            (None, None) => false,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -62,29 +326,386 @@ struct Project {
     name: String,
     tasks: Vec<Task>,
     expanded: bool,
+    #[serde(default)]
+    wip_limit: Option<usize>, // Max incomplete tasks before adding is blocked
+    #[serde(default)]
+    pinned: bool, // Pinned projects are listed first
+    #[serde(default)]
+    sort: TaskSort,
+    #[serde(default)]
+    description: String, // Shown under the header while expanded
+}
+
+// Display order for a project's tasks; the stored order is the manual one
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum TaskSort {
+    #[default]
+    Manual,
+    DueDate, // Earliest first, undated last
+}
+
+impl Project {
+    fn open_task_count(&self) -> usize {
+        self.tasks.iter().filter(|t| !t.completed).count()
+    }
+
+    /// Tasks (added, completed) in the 7 days up to `now`. Untimestamped tasks don't count.
+    fn week_activity(&self, now: DateTime<Local>) -> (usize, usize) {
+        let since = now - chrono::Duration::days(7);
+        let in_week = |at: Option<DateTime<Local>>| at.is_some_and(|at| at >= since);
+        let added = self.tasks.iter().filter(|t| in_week(t.created_at)).count();
+        let completed = self
+            .tasks
+            .iter()
+            .filter(|t| t.completed && in_week(t.completed_at))
+            .count();
+        (added, completed)
+    }
+
+    /// The unfinished task that `task` is waiting on, if any.
+    fn open_blocker(&self, task: &Task) -> Option<&Task> {
+        let blocker_id = task.blocked_by?;
+        self.tasks
+            .iter()
+            .find(|t| t.id == blocker_id && !t.completed)
+    }
+
+    /// Estimated minutes of the open tasks; tasks without an estimate don't count.
+    fn remaining_estimate(&self) -> u32 {
+        self.tasks
+            .iter()
+            .filter(|t| !t.completed)
+            .filter_map(|t| t.estimate_minutes)
+            .sum()
+    }
+
+    /// Whether `task_id` waits, directly or through other blockers, on `other_id`.
+    /// `task_id` itself counts, so it can't be made to wait on itself either.
+    fn depends_on(&self, task_id: usize, other_id: usize) -> bool {
+        let mut current = Some(task_id);
+        // At most one step per task, in case older data already has a loop
+        for _ in 0..=self.tasks.len() {
+            match current {
+                Some(id) if id == other_id => return true,
+                Some(id) => {
+                    current = self
+                        .tasks
+                        .iter()
+                        .find(|t| t.id == id)
+                        .and_then(|t| t.blocked_by)
+                }
+                None => return false,
+            }
+        }
+        false
+    }
+
+    fn at_wip_limit(&self) -> bool {
+        self.wip_limit
+            .is_some_and(|limit| self.open_task_count() >= limit)
+    }
+
+    /// How many more open tasks fit under the WIP limit; `None` without a limit.
+    fn open_slots(&self) -> Option<usize> {
+        self.wip_limit
+            .map(|limit| limit.saturating_sub(self.open_task_count()))
+    }
+
+    /// Rewrites the stored order to match the current sort, then goes back to manual.
+    fn persist_sort(&mut self) {
+        if self.sort == TaskSort::DueDate {
+            self.tasks.sort_by_key(due_date_key);
+        }
+        self.sort = TaskSort::Manual;
+    }
+}
+
+// Deleted tasks/projects, kept with enough context to put them back
+const TRASH_CAPACITY: usize = 100;
+const TRASH_RETENTION_DAYS: u32 = 30; // Default age at which trash entries are purged
+const TRASH_PURGE_INTERVAL_SECS: f64 = 3600.0; // Expired entries are purged at startup and hourly
+
+#[derive(Clone, Serialize, Deserialize)]
+enum TrashItem {
+    Task {
+        project_id: usize,
+        project_name: String,
+        index: usize,
+        task: Task,
+        #[serde(default)]
+        deleted_at: Option<DateTime<Local>>, // None in older saves
+    },
+    Project {
+        index: usize,
+        project: Project,
+        #[serde(default)]
+        deleted_at: Option<DateTime<Local>>,
+    },
+}
+
+impl TrashItem {
+    fn deleted_at(&self) -> Option<DateTime<Local>> {
+        match self {
+            TrashItem::Task { deleted_at, .. } | TrashItem::Project { deleted_at, .. } => {
+                *deleted_at
+            }
+        }
+    }
+}
+
+// JSON export file. A full todo_data.json also parses as one (extra fields are ignored).
+// A single-project export is just the `Project` object on its own.
+#[derive(Serialize, Deserialize)]
+struct TodoExport {
+    projects: Vec<Project>,
+}
+
+// Date range of the flat "focus" view that replaces the project list
+#[derive(Clone, Copy, PartialEq, Hash)]
+enum FocusRange {
+    Today,
+    Week, // Today and the next 6 days
+}
+
+// Completion status shown by the status dropdown
+#[derive(Clone, Copy, Default, PartialEq, Hash, Serialize, Deserialize)]
+enum StatusFilter {
+    #[default]
+    All,
+    Active, // Open tasks only
+    Done,   // Completed tasks only
+}
+
+impl StatusFilter {
+    const ALL: [StatusFilter; 3] = [StatusFilter::All, StatusFilter::Active, StatusFilter::Done];
+
+    fn label(self, lang: Lang) -> &'static str {
+        t(
+            lang,
+            match self {
+                StatusFilter::All => Tr::StatusAll,
+                StatusFilter::Active => Tr::StatusActive,
+                StatusFilter::Done => Tr::StatusDone,
+            },
+        )
+    }
+
+    fn matches(self, task: &Task) -> bool {
+        match self {
+            StatusFilter::All => true,
+            StatusFilter::Active => !task.completed,
+            StatusFilter::Done => task.completed,
+        }
+    }
+}
+
+// Text inputs that warn when submitted blank
+#[derive(Clone, Copy, PartialEq)]
+enum BlankInput {
+    ProjectName,
+    QuickAdd,
+    NewTask(usize),   // Right-click entry of a project
+    DraftTask(usize), // "Add a task..." row of a project
+}
+
+// How long the blank-input warning stays up (the last half second fades out)
+const BLANK_WARNING_SECS: f64 = 2.0;
+
+// Todo state captured before a destructive change, restored by Ctrl+Z
+const UNDO_LIMIT: usize = 50;
+
+struct UndoSnapshot {
+    projects: Vec<Project>,
+    trash: Vec<TrashItem>,
+    next_project_id: usize,
+    next_task_id: usize,
+}
+
+// An edit the user asked to start while another one had unsaved changes
+enum PendingEdit {
+    Project(usize, String),     // (project_id, text)
+    Task(usize, usize, String), // (project_id, task_id, text)
+}
+
+// How long a row flashes after its task is checked off
+const COMPLETION_FLASH_SECS: f64 = 0.6;
+
+// Accent color presets offered in the settings, as (name, rgb)
+const ACCENT_PRESETS: [(&str, [u8; 3]); 5] = [
+    ("Blue", [40, 120, 220]),
+    ("Green", [40, 160, 90]),
+    ("Purple", [140, 80, 200]),
+    ("Orange", [230, 130, 30]),
+    ("Pink", [220, 70, 140]),
+];
+
+// Open tasks older than this many days get a "stale" marker by default
+const STALE_AFTER_DAYS: u32 = 30;
+
+fn default_stale_after_days() -> Option<u32> {
+    Some(STALE_AFTER_DAYS)
+}
+
+fn default_trash_retention_days() -> u32 {
+    TRASH_RETENTION_DAYS
+}
+
+// Left-hand project navigation panel
+const PROJECT_PANEL_WIDTH: f32 = 180.0;
+
+fn default_project_panel_width() -> f32 {
+    PROJECT_PANEL_WIDTH
+}
+
+fn default_show_project_panel() -> bool {
+    true
+}
+
+fn default_new_projects_expanded() -> bool {
+    true
 }
 
 #[derive(Serialize, Deserialize)]
 struct TodoApp {
+    #[serde(skip)]
+    storage_error: Option<String>, // Startup banner when saved data failed to load
+    #[serde(skip)]
+    storage_locked: bool, // Saved data is from a newer app version; never overwrite it
+    #[serde(default)]
+    schema_version: u32, // 0 for saves made before versioning
     projects: Vec<Project>,
     next_project_id: usize,
     next_task_id: usize,
+    #[serde(default)]
+    trash: Vec<TrashItem>, // Oldest first
+    #[serde(default = "default_trash_retention_days")]
+    trash_retention_days: u32, // 0 keeps trash entries until the capacity pushes them out
     #[serde(skip)]
-    new_project_name: String,
+    trash_retention_draft: Option<u32>, // Value being dragged/typed in settings, not applied yet
+    #[serde(skip)]
+    last_trash_purge: Option<f64>, // Input time of the last periodic purge
+    #[serde(skip)]
+    show_trash: bool,
+    #[serde(skip)]
+    show_stats: bool,
+    #[serde(skip)]
+    show_settings: bool,
+    #[serde(skip)]
+    show_shortcuts: bool,
+    #[serde(skip)]
+    presentation_mode: bool, // Read-only view for screen sharing
+    #[serde(skip)]
+    clear_confirm_text: Option<String>, // Some while the "Clear all" confirmation is open
+    #[serde(skip)]
+    paste_target: Option<usize>, // Project receiving a bulk paste
+    #[serde(skip)]
+    paste_text: String,
+    #[serde(skip)]
+    pending_import: Option<Vec<Project>>, // Parsed import waiting for Replace/Merge
+    #[serde(skip)]
+    import_error: Option<String>,
+    #[serde(skip)]
+    export_error: Option<String>, // Shown until dismissed when writing an export fails
+    #[serde(default)]
+    new_project_name: String, // Draft kept across restarts until the project is added
+    #[serde(skip)]
+    project_name_error: Option<String>, // Shown next to the new-project input
+    #[serde(skip)]
+    blank_warning: Option<(BlankInput, f64)>, // (input, input time until which it shows)
+    #[serde(skip)]
+    search_query: String,
+    #[serde(skip)]
+    project_search: HashMap<usize, String>, // project_id -> query applied to that project only
+    #[serde(default)]
+    fuzzy_search: bool, // Subsequence matching instead of plain substring
+    #[serde(default)]
+    lang: Lang,
+    #[serde(default)]
+    accent: Option<[u8; 3]>, // None keeps egui's own selection/link colors
+    #[serde(skip)]
+    accent_applied: bool, // Style is up to date with `accent`
+    #[serde(default)]
+    accordion_mode: bool, // Expanding a project collapses all the others
+    #[serde(default = "default_new_projects_expanded")]
+    new_projects_expanded: bool,
+    #[serde(default)]
+    rapid_task_entry: bool, // The inline "New Task" entry stays open after adding
+    #[serde(default = "default_stale_after_days")]
+    stale_after_days: Option<u32>, // None turns the stale marker off
+    #[serde(default)]
+    completion_feedback: bool, // Flash a task row when it is checked off
+    #[serde(skip)]
+    completion_flash: Option<(usize, f64)>, // (task_id, input time the flash started)
+    #[serde(default = "default_show_project_panel")]
+    show_project_panel: bool,
+    #[serde(default = "default_project_panel_width")]
+    project_panel_width: f32,
+    #[serde(default)]
+    status_filter: StatusFilter,
+    #[serde(skip)]
+    overdue_only: bool,
+    #[serde(skip)]
+    flagged_only: bool,
+    #[serde(skip)]
+    show_snoozed: bool, // Snoozed tasks are hidden until they come due
+    #[serde(skip)]
+    snooze_pick_date: NaiveDate, // Date picked in the "Snooze" menu
+    #[serde(skip)]
+    focus_view: Option<FocusRange>, // Flat due-date list instead of the projects
+    #[serde(default)]
+    smart_order: bool, // Open tasks by due date, flagged first, in every project
+    #[serde(skip)]
+    quick_add_text: String,
+    #[serde(skip)]
+    quick_add_project: Option<usize>, // Target project for the quick-add bar
     #[serde(skip)]
     editing_project: Option<usize>,
     #[serde(skip)]
     editing_task: Option<(usize, usize)>, // (project_id, task_id)
-    #[serde(skip)]
-    new_task_texts: HashMap<usize, String>, // project_id -> new task text
+    #[serde(default)]
+    new_task_texts: HashMap<usize, String>, // project_id -> draft for the "Add task" row
     #[serde(skip)]
     edit_project_text: String,
     #[serde(skip)]
+    edit_project_original: String, // Name when editing started, to spot unsaved changes
+    #[serde(skip)]
+    edit_project_wip_limit: Option<usize>,
+    #[serde(skip)]
+    edit_project_description: String,
+    #[serde(skip)]
+    edit_project_error: Option<String>, // Shown next to the rename input
+    #[serde(skip)]
     edit_task_text: String,
     #[serde(skip)]
+    edit_task_original: String,
+    #[serde(skip)]
+    pending_edit: Option<PendingEdit>, // Waiting on the keep/discard prompt
+    #[serde(skip)]
+    edit_task_due_date: Option<NaiveDate>,
+    #[serde(skip)]
+    edit_task_due_time: Option<NaiveTime>,
+    #[serde(skip)]
+    edit_task_blocked_by: Option<usize>,
+    #[serde(skip)]
+    edit_task_estimate: Option<u32>, // Minutes
+    #[serde(skip)]
+    fired_reminders: HashSet<usize>, // Task IDs already notified this session
+    #[serde(skip)]
+    focused_project: Option<usize>, // Project header targeted by keyboard shortcuts
+    #[serde(skip)]
+    selected_task: Option<(usize, usize)>, // (project_id, task_id) targeted by the Delete key
+    #[serde(skip)]
+    undo_stack: Vec<UndoSnapshot>, // Oldest first
+    #[serde(skip)]
+    detached_projects: HashSet<usize>, // Projects popped out into their own window
+    #[serde(skip)]
+    scroll_to_project: Option<usize>, // Scroll this project into view on the next frame
+    #[serde(default)]
     adding_task_to_project: Option<usize>, // Project ID for right-click task creation
+    #[serde(default)]
+    right_click_task_text: HashMap<usize, String>, // Unsubmitted text of each project's right-click entry
     #[serde(skip)]
-    right_click_task_text: HashMap<usize, String>, // Task text for each project's right-click creation
+    focus_add_task_field: bool, // Focus the inline "New Task" field on its first frame
     // Notes canvas fields
     notes_canvas: NotesCanvas,
     #[serde(skip)]
@@ -102,22 +723,102 @@ struct TodoApp {
     editing_title: Option<usize>, // ID of textbox whose title is being edited
     #[serde(skip)]
     temp_title_text: String,
+
+    // Unsaved-changes tracking for the quit confirmation
+    #[serde(skip)]
+    dirty: bool,
+    #[serde(skip)]
+    show_quit_dialog: bool,
+    #[serde(skip)]
+    allow_close: bool,
+    #[serde(skip)]
+    discard_on_close: bool,
+    #[serde(skip)]
+    saved_toast_until: Option<f64>, // Input time until which the "Saved" toast shows
+    #[serde(skip)]
+    title_open_count: Option<usize>, // Open task count last shown in the window title
+    #[serde(skip)]
+    row_heights: HashMap<egui::Id, (u64, f32)>, // Row -> (content stamp, height) when last drawn
+    #[serde(skip)]
+    rows_seen: HashSet<egui::Id>, // Rows drawn or skipped this frame
+    #[serde(skip)]
+    rows_view: u64, // Stamp of the filters and modes the heights were measured under
 }
 
 impl Default for TodoApp {
     fn default() -> Self {
         Self {
+            storage_error: None,
+            storage_locked: false,
+            schema_version: SCHEMA_VERSION,
             projects: Vec::new(),
             next_project_id: 1,
             next_task_id: 1,
+            trash: Vec::new(),
+            trash_retention_days: TRASH_RETENTION_DAYS,
+            trash_retention_draft: None,
+            last_trash_purge: None,
+            show_trash: false,
+            show_stats: false,
+            show_settings: false,
+            show_shortcuts: false,
+            presentation_mode: false,
+            clear_confirm_text: None,
+            paste_target: None,
+            paste_text: String::new(),
+            pending_import: None,
+            import_error: None,
+            export_error: None,
             new_project_name: String::new(),
+            project_name_error: None,
+            blank_warning: None,
+            search_query: String::new(),
+            project_search: HashMap::new(),
+            fuzzy_search: false,
+            lang: Lang::English,
+            accent: None,
+            accent_applied: false,
+            accordion_mode: false,
+            new_projects_expanded: true,
+            rapid_task_entry: false,
+            stale_after_days: Some(STALE_AFTER_DAYS),
+            completion_feedback: false,
+            completion_flash: None,
+            show_project_panel: true,
+            project_panel_width: PROJECT_PANEL_WIDTH,
+            status_filter: StatusFilter::All,
+            overdue_only: false,
+            flagged_only: false,
+            show_snoozed: false,
+            snooze_pick_date: Local::now().date_naive(),
+            focus_view: None,
+            smart_order: false,
+            quick_add_text: String::new(),
+            quick_add_project: None,
             editing_project: None,
             editing_task: None,
             new_task_texts: HashMap::new(),
             edit_project_text: String::new(),
+            edit_project_original: String::new(),
+            edit_project_wip_limit: None,
+            edit_project_description: String::new(),
+            edit_project_error: None,
             edit_task_text: String::new(),
+            edit_task_original: String::new(),
+            pending_edit: None,
+            edit_task_due_date: None,
+            edit_task_due_time: None,
+            edit_task_blocked_by: None,
+            edit_task_estimate: None,
+            fired_reminders: HashSet::new(),
+            focused_project: None,
+            selected_task: None,
+            undo_stack: Vec::new(),
+            detached_projects: HashSet::new(),
+            scroll_to_project: None,
             adding_task_to_project: None,
             right_click_task_text: HashMap::new(),
+            focus_add_task_field: false,
             notes_canvas: NotesCanvas::default(),
             show_notes: false,
             context_menu_pos: None,
@@ -125,6 +826,15 @@ impl Default for TodoApp {
             commonmark_cache: CommonMarkCache::default(),
             editing_title: None,
             temp_title_text: String::new(),
+            dirty: false,
+            show_quit_dialog: false,
+            allow_close: false,
+            discard_on_close: false,
+            saved_toast_until: None,
+            title_open_count: None,
+            row_heights: HashMap::new(),
+            rows_seen: HashSet::new(),
+            rows_view: 0,
         }
     }
 }
@@ -139,36 +849,121 @@ impl TodoApp {
         // Release: Use eframe's default storage (OS standard paths)
         // Debug: Use local file "todo_data.json" in CWD
 
-        let loaded_app: Option<Self> = if cfg!(debug_assertions) {
+        // Raw saved data plus where a backup of it should go if it can't be parsed
+        let (raw, backup_path): (Option<String>, PathBuf) = if cfg!(debug_assertions) {
             // Debug Mode: Try loading from local file
-            if let Ok(file) = File::open("todo_data.json") {
-                let reader = BufReader::new(file);
-                serde_json::from_reader(reader).ok()
-            } else {
-                None
-            }
+            (
+                std::fs::read_to_string("todo_data.json").ok(),
+                PathBuf::from("todo_data.backup.json"),
+            )
         } else {
             // Release Mode: Use eframe storage
-            if let Some(storage) = cc.storage {
-                eframe::get_value(storage, eframe::APP_KEY)
-            } else {
-                None
-            }
+            (
+                cc.storage.and_then(|s| s.get_string(eframe::APP_KEY)),
+                eframe::storage_dir(APP_NAME)
+                    .unwrap_or_default()
+                    .join("todo_data.backup.json"),
+            )
         };
 
+        // Parsed untyped first, so `migrate` can rework older layouts
+        let saved: Option<serde_json::Value> = raw.as_deref().and_then(|raw| {
+            serde_json::from_str(raw).ok().or_else(|| {
+                // Release builds used to store RON through eframe; the current
+                // types still read those saves
+                if cfg!(debug_assertions) {
+                    return None;
+                }
+                cc.storage
+                    .and_then(|s| eframe::get_value::<Self>(s, eframe::APP_KEY))
+                    .and_then(|app| serde_json::to_value(app).ok())
+            })
+        });
+        let saved_version = saved.as_ref().map_or(0, saved_schema_version);
+        let loaded_app: Option<Self> = saved.and_then(|value| Self::migrate(value).ok());
+
+        // Written by a newer version of the app: show what this one can read,
+        // but never save over it
+        let newer_message = (saved_version > SCHEMA_VERSION).then(|| {
+            format!(
+                "Your data was saved by a newer version of the app (format {}, this one \
+                 reads up to {}). Changes made now won't be saved, so it isn't overwritten.",
+                saved_version, SCHEMA_VERSION
+            )
+        });
+        if newer_message.is_some() && loaded_app.is_none() {
+            return Self {
+                storage_error: newer_message,
+                storage_locked: true,
+                ..Default::default()
+            };
+        }
+
+        // Saved data exists but didn't parse: keep a copy before starting empty,
+        // since the next save would overwrite it
+        if let (Some(raw), None) = (&raw, &loaded_app) {
+            let message = match std::fs::write(&backup_path, raw) {
+                Ok(()) => format!(
+                    "Your saved data couldn't be read; a backup was saved to {}.",
+                    backup_path.display()
+                ),
+                Err(err) => format!(
+                    "Your saved data couldn't be read, and backing it up failed: {}.",
+                    err
+                ),
+            };
+            return Self {
+                storage_error: Some(message),
+                ..Default::default()
+            };
+        }
+
         if let Some(mut app) = loaded_app {
+            if newer_message.is_some() {
+                app.storage_error = newer_message;
+                app.storage_locked = true;
+            }
             // Restore transient/runtime state
             app.commonmark_cache = CommonMarkCache::default();
             app.editing_textbox = None;
             app.editing_title = None;
-            app.adding_task_to_project = None;
             app.editing_task = None;
-            app.right_click_task_text = HashMap::new();
-            app.new_task_texts = HashMap::new();
+
+            // Unsubmitted right-click drafts survive a restart, as long as their project does
+            let project_ids: HashSet<usize> = app.projects.iter().map(|p| p.id).collect();
+            app.right_click_task_text
+                .retain(|id, text| project_ids.contains(id) && !text.is_empty());
+            match app.adding_task_to_project {
+                Some(id) if project_ids.contains(&id) => {
+                    app.right_click_task_text.entry(id).or_default();
+                }
+                _ => app.adding_task_to_project = None,
+            }
+            // Entries from before deletion times were recorded count from now
+            let now = Local::now();
+            for item in &mut app.trash {
+                match item {
+                    TrashItem::Task { deleted_at, .. } | TrashItem::Project { deleted_at, .. } => {
+                        deleted_at.get_or_insert(now);
+                    }
+                }
+            }
+            app.purge_expired_trash(now);
+
             app.context_menu_pos = None;
             app.temp_title_text = String::new();
             app.edit_task_text = String::new();
 
+            // Tasks that were already overdue before launch don't get a reminder
+            let now = Local::now().naive_local();
+            app.fired_reminders = app
+                .projects
+                .iter()
+                .flat_map(|p| &p.tasks)
+                .filter(|t| t.due_at().is_some_and(|due| due <= now))
+                .map(|t| t.id)
+                .collect();
+
             // Ensure auto_height is set correctly for old data if needed (though serde default handles it)
             // Fix text boxes that might have come from older saves without auto_height
             // (already handled by serde default)
@@ -179,14 +974,33 @@ impl TodoApp {
         // If load failed or no save exists, return default
         Default::default()
     }
-}
 
-impl eframe::App for TodoApp {
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+    /// Brings saved data up to `SCHEMA_VERSION`, one step per version, then parses
+    /// it. Data from a newer version is parsed as it is.
+    fn migrate(mut value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        let version = saved_schema_version(&value);
+        if version < 1 {
+            // v0 -> v1: versioning introduced. Nothing was renamed; new fields are
+            // filled by serde defaults
+        }
+        if version < SCHEMA_VERSION {
+            if let Some(fields) = value.as_object_mut() {
+                fields.insert("schema_version".into(), SCHEMA_VERSION.into());
+            }
+        }
+        serde_json::from_value(value)
+    }
+
+    fn persist(&self, storage: Option<&mut (dyn eframe::Storage + '_)>) {
         // --- Persistence Saving Strategy ---
         // Release: Use eframe's default storage
         // Debug: Use local file "todo_data.json"
 
+        // Data from a newer app version stays as it is
+        if self.storage_locked {
+            return;
+        }
+
         if cfg!(debug_assertions) {
             // Debug Mode: Save to local file
             if let Ok(file) = File::create("todo_data.json") {
@@ -194,29 +1008,144 @@ impl eframe::App for TodoApp {
                 // Pretty print for easier debugging
                 let _ = serde_json::to_writer_pretty(writer, self);
             }
-        } else {
-            // Release Mode: Save to eframe storage
-            eframe::set_value(storage, eframe::APP_KEY, self);
+        } else if let Some(storage) = storage {
+            // Release Mode: Save to eframe storage, as JSON like the debug file so
+            // `migrate` can read it untyped
+            if let Ok(json) = serde_json::to_string(self) {
+                storage.set_string(eframe::APP_KEY, json);
+            }
+            storage.flush();
+        }
+    }
+}
+
+impl eframe::App for TodoApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // The user chose "Discard" in the quit dialog
+        if self.discard_on_close {
+            return;
         }
+        self.persist(Some(storage));
+        self.dirty = false;
     }
 
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Render floating pen button (always visible)
-        self.render_floating_pen_button(ctx);
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Hold the window open while there are unsaved changes
+        if ctx.input(|i| i.viewport().close_requested()) && self.dirty && !self.allow_close {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.show_quit_dialog = true;
+        }
+
+        // Ctrl+S: save right away (ignored while typing in a field)
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::S))
+        {
+            self.persist(frame.storage_mut());
+            self.dirty = false;
+            if !self.storage_locked {
+                self.saved_toast_until = Some(ctx.input(|i| i.time) + 1.5);
+            }
+        }
+
+        // F1 or "?": shortcut list (also ignored while typing)
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| {
+                i.consume_key(egui::Modifiers::NONE, egui::Key::F1)
+                    || i.events
+                        .iter()
+                        .any(|e| matches!(e, egui::Event::Text(t) if t == "?"))
+            })
+        {
+            self.show_shortcuts = !self.show_shortcuts;
+        }
+
+        self.apply_accent(ctx);
+
+        self.check_reminders(ctx);
+
+        // Startup already purged; after that, check once an hour
+        let time = ctx.input(|i| i.time);
+        match self.last_trash_purge {
+            None => self.last_trash_purge = Some(time),
+            Some(last) if time - last >= TRASH_PURGE_INTERVAL_SECS => {
+                self.purge_expired_trash(Local::now());
+                self.last_trash_purge = Some(time);
+            }
+            Some(_) => {}
+        }
+
+        self.update_window_title(ctx);
+
+        self.begin_lazy_rows();
+
+        self.render_storage_error_banner(ctx);
+
+        // Render floating pen button (hidden while presenting; notes are editable)
+        if !self.presentation_mode {
+            self.render_floating_pen_button(ctx);
+        }
 
         // Conditionally render main content
         if self.show_notes {
             self.render_notes_canvas(ctx);
         } else {
+            self.handle_project_shortcuts(ctx);
             self.render_todo_view(ctx);
         }
-    }
-}
 
-impl TodoApp {
-    fn render_floating_pen_button(&mut self, ctx: &egui::Context) {
-        egui::Area::new(egui::Id::new("floating_pen_button"))
-            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-20.0, -20.0))
+        self.render_detached_projects(ctx);
+
+        if self.show_trash {
+            self.render_trash_window(ctx);
+        }
+
+        if self.show_stats {
+            self.render_stats_window(ctx);
+        }
+
+        if self.paste_target.is_some() {
+            self.render_paste_window(ctx);
+        }
+
+        if !self.presentation_mode {
+            self.handle_dropped_files(ctx);
+        }
+        if self.pending_import.is_some() || self.import_error.is_some() {
+            self.render_import_window(ctx);
+        }
+
+        if self.export_error.is_some() {
+            self.render_export_error_window(ctx);
+        }
+
+        if self.pending_edit.is_some() {
+            self.render_discard_edit_dialog(ctx);
+        }
+
+        if self.show_settings {
+            self.render_settings_window(ctx);
+        }
+
+        if self.show_shortcuts {
+            self.render_shortcuts_window(ctx);
+        }
+
+        if self.clear_confirm_text.is_some() {
+            self.render_clear_all_dialog(ctx, frame);
+        }
+
+        if self.show_quit_dialog {
+            self.render_quit_dialog(ctx, frame);
+        }
+
+        self.render_saved_toast(ctx);
+    }
+}
+
+impl TodoApp {
+    fn render_floating_pen_button(&mut self, ctx: &egui::Context) {
+        egui::Area::new(egui::Id::new("floating_pen_button"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-20.0, -20.0))
             .show(ctx, |ui| {
                 let button_size = 60.0;
                 let pen_icon = if self.show_notes {
@@ -238,474 +1167,3225 @@ impl TodoApp {
             });
     }
 
-    fn render_todo_view(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // Fixed font sizes
-            let heading_size = 24.0;
-            let project_title_size = 20.0; // Larger font for project titles
-            let label_size = 16.0;
-            let button_size = 14.0;
-            let text_size = 16.0; // Increased task text size for better visibility
+    /// Fires a desktop notification once per task when its due time passes.
+    fn check_reminders(&mut self, ctx: &egui::Context) {
+        let now = Local::now().naive_local();
+        for task in self.projects.iter().flat_map(|p| &p.tasks) {
+            let Some(due) = task.due_at() else {
+                continue;
+            };
+            if task.completed || due > now || !self.fired_reminders.insert(task.id) {
+                continue;
+            }
 
-            ui.horizontal(|ui| {
-                // Left side - Username
-                ui.label(
-                    egui::RichText::new(format!(
-                        "User: {}",
-                        whoami::username().unwrap_or_else(|_| "Unknown".to_string())
-                    ))
-                    .size(label_size),
-                );
+            let body = task.text.clone();
+            // Keep the (D-Bus / OS) round trip off the UI thread
+            std::thread::spawn(move || {
+                let _ = notify_rust::Notification::new()
+                    .summary("Task due")
+                    .body(&body)
+                    .appname("Todo App")
+                    .show();
+            });
+        }
 
-                // Get the remaining width for the rest of the layout
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    // Right side - Current date with padding (from right to left)
-                    ui.add_space(10.0); // Padding from right edge
-                    let now = Local::now();
-                    ui.label(
-                        egui::RichText::new(format!("{}", now.format("%d/%m/%Y"))).size(label_size),
-                    );
+        // Wake up periodically even when the window is idle
+        ctx.request_repaint_after(std::time::Duration::from_secs(30));
+    }
 
-                    // Center the title in remaining space
-                    ui.with_layout(
-                        egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
-                        |ui| {
-                            ui.heading(egui::RichText::new("Todo App").size(heading_size));
-                        },
-                    );
-                });
-            });
-            ui.separator();
+    /// Recolors selections, links and active widgets in both the light and dark
+    /// styles, so the accent survives a system theme switch.
+    fn apply_accent(&mut self, ctx: &egui::Context) {
+        if self.accent_applied {
+            return;
+        }
+        self.accent_applied = true;
 
-            // Add new project section
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("New Project:").size(label_size));
-                let response = ui.text_edit_singleline(&mut self.new_project_name);
+        let accent = self.accent;
+        ctx.all_styles_mut(|style| {
+            let base = if style.visuals.dark_mode {
+                egui::Visuals::dark()
+            } else {
+                egui::Visuals::light()
+            };
+            let visuals = &mut style.visuals;
+            let Some([r, g, b]) = accent else {
+                visuals.selection = base.selection;
+                visuals.hyperlink_color = base.hyperlink_color;
+                visuals.widgets.hovered.bg_stroke = base.widgets.hovered.bg_stroke;
+                visuals.widgets.active.bg_fill = base.widgets.active.bg_fill;
+                return;
+            };
 
-                if ui
-                    .button(
-                        egui::RichText::new(format!("{} Add Project", icons::icons::ICON_ADD))
-                            .size(button_size),
-                    )
-                    .clicked()
-                    || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
-                {
-                    self.add_project();
-                }
-            });
+            let color = egui::Color32::from_rgb(r, g, b);
+            // Text on the accent needs to stay readable
+            let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            let on_accent = if luminance > 150.0 {
+                egui::Color32::BLACK
+            } else {
+                egui::Color32::WHITE
+            };
+            visuals.selection.bg_fill = color;
+            visuals.selection.stroke.color = on_accent;
+            visuals.hyperlink_color = color;
+            visuals.widgets.hovered.bg_stroke.color = color;
+            visuals.widgets.active.bg_fill = color;
+        });
+    }
 
-            ui.add_space(16.0);
+    /// Shows the open task count in the window (and taskbar) title, e.g. "Todo App (3)".
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let open_count = self.projects.iter().map(Project::open_task_count).sum();
+        // Only talk to the window when the number actually changes
+        if self.title_open_count == Some(open_count) {
+            return;
+        }
+        self.title_open_count = Some(open_count);
+
+        let title = if open_count == 0 {
+            APP_NAME.to_string()
+        } else {
+            format!("{} ({})", APP_NAME, open_count)
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+    }
+
+    fn render_storage_error_banner(&mut self, ctx: &egui::Context) {
+        let Some(message) = &self.storage_error else {
+            return;
+        };
 
-            // Simplified instruction for users
+        let mut dismissed = false;
+        egui::TopBottomPanel::top("storage_error_banner").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                ui.label(
-                    egui::RichText::new(format!(
-                        "Right-click on the expand/collapse button ({}/{}) to add tasks directly!",
-                        icons::icons::ICON_CHEVRON_RIGHT,
-                        icons::icons::ICON_EXPAND_MORE
-                    ))
-                    .size(label_size)
-                    .color(egui::Color32::GRAY),
+                ui.colored_label(
+                    egui::Color32::from_rgb(230, 150, 0),
+                    format!("{} {}", icons::icons::ICON_WARNING, message),
                 );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(icons::icons::ICON_CLOSE).clicked() {
+                        dismissed = true;
+                    }
+                });
             });
+        });
+        if dismissed {
+            self.storage_error = None;
+        }
+    }
 
-            ui.add_space(16.0);
+    fn render_saved_toast(&mut self, ctx: &egui::Context) {
+        let Some(until) = self.saved_toast_until else {
+            return;
+        };
+        let now = ctx.input(|i| i.time);
+        if now >= until {
+            self.saved_toast_until = None;
+            return;
+        }
 
-            ui.separator();
+        egui::Area::new(egui::Id::new("saved_toast"))
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -20.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(format!("{} Saved", icons::icons::ICON_CHECK))
+                            .size(LABEL_SIZE),
+                    );
+                });
+            });
+        // Wake up again to hide the toast
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(until - now));
+    }
 
-            // Display projects in a scroll area
-            let (projects_to_remove, project_actions, task_actions) = egui::ScrollArea::vertical()
-                .auto_shrink([false, true])
-                .show(ui, |ui| {
-                    let mut projects_to_remove = Vec::new();
-                    let mut project_actions = Vec::new(); // Store actions to perform after iteration
-                    let mut task_actions = Vec::new(); // Store task actions
-
-                    for (project_idx, project) in self.projects.iter_mut().enumerate() {
-                        ui.push_id(project.id, |ui| {
-                            let _frame_response = egui::Frame::group(ui.style())
-                                .inner_margin(egui::Margin::same(16))
-                                .show(ui, |ui| {
-                                    ui.set_width(ui.available_width());
-                                    // Project header
-                                    ui.horizontal(|ui| {
-                                        // Expand/collapse button with right-click to add task
-                                        let expand_icon = if project.expanded {
-                                            icons::icons::ICON_EXPAND_MORE
-                                        } else {
-                                            icons::icons::ICON_CHEVRON_RIGHT
-                                        };
-                                        let expand_response = ui.button(
-                                            egui::RichText::new(expand_icon).size(button_size),
-                                        );
+    fn project_edit_dirty(&self) -> bool {
+        self.editing_project.is_some() && self.edit_project_text != self.edit_project_original
+    }
 
-                                        if expand_response.clicked() {
-                                            project.expanded = !project.expanded;
-                                        }
+    fn task_edit_dirty(&self) -> bool {
+        self.editing_task.is_some() && self.edit_task_text != self.edit_task_original
+    }
 
-                                        // Right-click on expand button to add task
-                                        if expand_response.secondary_clicked() {
-                                            project_actions.push((
-                                                "add_task",
-                                                project.id,
-                                                String::new(),
-                                            ));
-                                        }
+    fn render_discard_edit_dialog(&mut self, ctx: &egui::Context) {
+        let mut keep = false;
+        let mut discard = false;
 
-                                        // Project name and controls
-                                        if self.editing_project == Some(project.id) {
-                                            // Editing mode: show text input with confirmation buttons
-                                            let response = ui
-                                                .text_edit_singleline(&mut self.edit_project_text);
-                                            if response.lost_focus()
-                                                && ui.input(|i| i.key_pressed(egui::Key::Enter))
-                                            {
-                                                if !self.edit_project_text.trim().is_empty() {
-                                                    project.name = self.edit_project_text.clone();
-                                                }
-                                                project_actions.push((
-                                                    "stop_edit",
-                                                    project.id,
-                                                    String::new(),
-                                                ));
-                                            } else if response.lost_focus()
-                                                && ui.input(|i| i.key_pressed(egui::Key::Escape))
-                                            {
-                                                project_actions.push((
-                                                    "stop_edit",
-                                                    project.id,
-                                                    String::new(),
-                                                ));
-                                            }
+        egui::Window::new("Discard unsaved edit?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("The item you are editing has changes that were not saved.");
+                ui.horizontal(|ui| {
+                    if ui.button("Keep editing").clicked() {
+                        keep = true;
+                    }
+                    if ui.button("Discard").clicked() {
+                        discard = true;
+                    }
+                });
+            });
 
-                                            if ui.button(icons::icons::ICON_CHECK).clicked() {
-                                                if !self.edit_project_text.trim().is_empty() {
-                                                    project.name = self.edit_project_text.clone();
-                                                }
-                                                project_actions.push((
-                                                    "stop_edit",
-                                                    project.id,
-                                                    String::new(),
-                                                ));
-                                            }
-                                            if ui.button(icons::icons::ICON_CLOSE).clicked() {
-                                                project_actions.push((
-                                                    "stop_edit",
-                                                    project.id,
-                                                    String::new(),
-                                                ));
-                                            }
-                                        } else {
-                                            // Display mode: show label with edit button
-                                            ui.label(
-                                                egui::RichText::new(&project.name)
-                                                    .size(project_title_size),
-                                            );
-
-                                            ui.with_layout(
-                                                egui::Layout::right_to_left(egui::Align::Center),
-                                                |ui| {
-                                                    // Delete project button
-                                                    if ui
-                                                        .button(
-                                                            egui::RichText::new(
-                                                                icons::icons::ICON_DELETE,
-                                                            )
-                                                            .size(button_size),
-                                                        )
-                                                        .clicked()
-                                                    {
-                                                        projects_to_remove.push(project_idx);
-                                                    }
-
-                                                    // Edit project button
-                                                    if ui
-                                                        .button(
-                                                            egui::RichText::new(
-                                                                icons::icons::ICON_EDIT,
-                                                            )
-                                                            .size(button_size),
-                                                        )
-                                                        .clicked()
-                                                    {
-                                                        project_actions.push((
-                                                            "start_edit",
-                                                            project.id,
-                                                            project.name.clone(),
-                                                        ));
-                                                    }
-                                                },
-                                            );
-                                        }
-                                    });
+        if keep {
+            self.pending_edit = None;
+        }
+        if discard {
+            // With the old edit closed, the postponed start goes straight through
+            match self.pending_edit.take() {
+                Some(PendingEdit::Project(project_id, text)) => {
+                    self.editing_project = None;
+                    self.apply_project_action("start_edit", project_id, text);
+                }
+                Some(PendingEdit::Task(project_id, task_id, text)) => {
+                    self.editing_task = None;
+                    self.apply_task_action("start_edit", project_id, task_id, text);
+                }
+                None => {}
+            }
+        }
+    }
 
-                                    // Tasks (only shown when expanded)
-                                    if project.expanded {
-                                        ui.indent("tasks", |ui| {
-                                            let mut tasks_to_remove = Vec::new();
+    fn render_quit_dialog(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        egui::Window::new("Save before quitting?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("You have unsaved changes.");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        self.persist(frame.storage_mut());
+                        self.dirty = false;
+                        self.allow_close = true;
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                    if ui.button("Discard").clicked() {
+                        self.discard_on_close = true;
+                        self.allow_close = true;
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.show_quit_dialog = false;
+                    }
+                });
+            });
+    }
 
-                                            for (task_idx, task) in
-                                                project.tasks.iter_mut().enumerate()
-                                            {
-                                                ui.add_space(8.0);
-                                                ui.horizontal(|ui| {
-                                                    // Checkbox for completion
-                                                    ui.checkbox(&mut task.completed, "");
-
-                                                    // Task text and controls
-                                                    if self.editing_task
-                                                        == Some((project.id, task.id))
-                                                    {
-                                                        // Editing mode: show text input with confirmation buttons
-                                                        let response = ui.text_edit_singleline(
-                                                            &mut self.edit_task_text,
-                                                        );
-                                                        if response.lost_focus()
-                                                            && ui.input(|i| {
-                                                                i.key_pressed(egui::Key::Enter)
-                                                            })
-                                                        {
-                                                            if !self
-                                                                .edit_task_text
-                                                                .trim()
-                                                                .is_empty()
-                                                            {
-                                                                task.text =
-                                                                    self.edit_task_text.clone();
-                                                            }
-                                                            task_actions.push((
-                                                                "stop_edit",
-                                                                project.id,
-                                                                task.id,
-                                                                String::new(),
-                                                            ));
-                                                        } else if response.lost_focus()
-                                                            && ui.input(|i| {
-                                                                i.key_pressed(egui::Key::Escape)
-                                                            })
-                                                        {
-                                                            task_actions.push((
-                                                                "stop_edit",
-                                                                project.id,
-                                                                task.id,
-                                                                String::new(),
-                                                            ));
-                                                        }
-
-                                                        if ui
-                                                            .button(icons::icons::ICON_CHECK)
-                                                            .clicked()
-                                                        {
-                                                            if !self
-                                                                .edit_task_text
-                                                                .trim()
-                                                                .is_empty()
-                                                            {
-                                                                task.text =
-                                                                    self.edit_task_text.clone();
-                                                            }
-                                                            task_actions.push((
-                                                                "stop_edit",
-                                                                project.id,
-                                                                task.id,
-                                                                String::new(),
-                                                            ));
-                                                        }
-                                                        if ui
-                                                            .button(icons::icons::ICON_CLOSE)
-                                                            .clicked()
-                                                        {
-                                                            task_actions.push((
-                                                                "stop_edit",
-                                                                project.id,
-                                                                task.id,
-                                                                String::new(),
-                                                            ));
-                                                        }
-                                                    } else {
-                                                        // Display mode: show label with edit button
-                                                        let text_color = if task.completed {
-                                                            ui.visuals().weak_text_color()
-                                                        } else {
-                                                            ui.visuals().text_color()
-                                                        };
-                                                        ui.colored_label(
-                                                            text_color,
-                                                            egui::RichText::new(&task.text)
-                                                                .size(text_size),
-                                                        );
-
-                                                        ui.with_layout(
-                                                            egui::Layout::right_to_left(
-                                                                egui::Align::Center,
-                                                            ),
-                                                            |ui| {
-                                                                // Delete task button
-                                                                if ui
-                                                                    .button(
-                                                                        icons::icons::ICON_DELETE,
-                                                                    )
-                                                                    .clicked()
-                                                                {
-                                                                    tasks_to_remove.push(task_idx);
-                                                                }
-
-                                                                // Edit task button
-                                                                if ui
-                                                                    .button(icons::icons::ICON_EDIT)
-                                                                    .clicked()
-                                                                {
-                                                                    task_actions.push((
-                                                                        "start_edit",
-                                                                        project.id,
-                                                                        task.id,
-                                                                        task.text.clone(),
-                                                                    ));
-                                                                }
-                                                            },
-                                                        );
-                                                    }
-                                                });
-                                            }
+    fn render_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_settings;
 
-                                            // Remove tasks
-                                            for &idx in tasks_to_remove.iter().rev() {
-                                                project.tasks.remove(idx);
-                                            }
+        egui::Window::new(t(self.lang, Tr::Settings))
+            .id(egui::Id::new("settings_window"))
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(t(self.lang, Tr::Language))
+                        .size(LABEL_SIZE)
+                        .strong(),
+                );
+                let mut lang = self.lang;
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(lang.name())
+                    .show_ui(ui, |ui| {
+                        for option in Lang::ALL {
+                            ui.selectable_value(&mut lang, option, option.name());
+                        }
+                    });
+                if lang != self.lang {
+                    self.lang = lang;
+                    self.dirty = true;
+                }
+                ui.separator();
 
-                                            // Show inline task creation UI when this project is selected for task addition
-                                            if self.adding_task_to_project == Some(project.id) {
-                                                ui.add_space(8.0);
-                                                ui.horizontal(|ui| {
-                                                    ui.label("New Task:");
-                                                    let task_text = self
-                                                        .right_click_task_text
-                                                        .get_mut(&project.id)
-                                                        .unwrap();
-                                                    let response =
-                                                        ui.text_edit_singleline(task_text);
-
-                                                    if ui.button(icons::icons::ICON_CHECK).clicked()
-                                                        || (response.lost_focus()
-                                                            && ui.input(|i| {
-                                                                i.key_pressed(egui::Key::Enter)
-                                                            }))
-                                                    {
-                                                        if !task_text.trim().is_empty() {
-                                                            project_actions.push((
-                                                                "create_task",
-                                                                project.id,
-                                                                task_text.clone(),
-                                                            ));
-                                                        }
-                                                        project_actions.push((
-                                                            "cancel_add_task",
-                                                            project.id,
-                                                            String::new(),
-                                                        ));
-                                                    }
-
-                                                    if ui.button(icons::icons::ICON_CLOSE).clicked()
-                                                        || (response.lost_focus()
-                                                            && ui.input(|i| {
-                                                                i.key_pressed(egui::Key::Escape)
-                                                            }))
-                                                    {
-                                                        project_actions.push((
-                                                            "cancel_add_task",
-                                                            project.id,
-                                                            String::new(),
-                                                        ));
-                                                    }
-                                                });
-                                            }
-                                        });
-                                    }
-                                });
-                        });
-                        ui.add_space(16.0);
+                ui.label(
+                    egui::RichText::new(t(self.lang, Tr::AccentColor))
+                        .size(LABEL_SIZE)
+                        .strong(),
+                );
+                let mut accent = self.accent;
+                ui.horizontal_wrapped(|ui| {
+                    ui.selectable_value(&mut accent, None, "Default");
+                    for (name, rgb) in ACCENT_PRESETS {
+                        let [r, g, b] = rgb;
+                        let swatch = egui::Button::new("")
+                            .fill(egui::Color32::from_rgb(r, g, b))
+                            .min_size(egui::vec2(20.0, 20.0))
+                            .selected(accent == Some(rgb));
+                        if ui.add(swatch).on_hover_text(name).clicked() {
+                            accent = Some(rgb);
+                        }
                     }
-
-                    (projects_to_remove, project_actions, task_actions)
-                })
-                .inner;
-
-            // Process project actions
-            for (action, project_id, text) in project_actions {
-                match action {
-                    "start_edit" => {
-                        self.editing_project = Some(project_id);
-                        self.edit_project_text = text;
+                    // Custom color; starts from the current accent
+                    let mut custom = accent.unwrap_or(ACCENT_PRESETS[0].1);
+                    if ui
+                        .color_edit_button_srgb(&mut custom)
+                        .on_hover_text("Custom color")
+                        .changed()
+                    {
+                        accent = Some(custom);
                     }
-                    "stop_edit" => {
-                        self.editing_project = None;
+                });
+                if accent != self.accent {
+                    self.accent = accent;
+                    self.accent_applied = false;
+                    self.dirty = true;
+                }
+                ui.separator();
+
+                ui.label(
+                    egui::RichText::new(t(self.lang, Tr::ProjectsAndTasks))
+                        .size(LABEL_SIZE)
+                        .strong(),
+                );
+                if ui
+                    .checkbox(&mut self.accordion_mode, "Only one project open at a time")
+                    .changed()
+                {
+                    self.dirty = true;
+                }
+                if ui
+                    .checkbox(
+                        &mut self.new_projects_expanded,
+                        "Open new projects expanded",
+                    )
+                    .changed()
+                {
+                    self.dirty = true;
+                }
+                if ui
+                    .checkbox(
+                        &mut self.rapid_task_entry,
+                        "Keep the \"New Task\" entry open after adding",
+                    )
+                    .on_hover_text("Enter adds the task and clears the field for the next one")
+                    .changed()
+                {
+                    self.dirty = true;
+                }
+                ui.horizontal(|ui| {
+                    let mut warn_stale = self.stale_after_days.is_some();
+                    if ui
+                        .checkbox(&mut warn_stale, "Mark open tasks stale after")
+                        .changed()
+                    {
+                        self.stale_after_days = warn_stale.then_some(STALE_AFTER_DAYS);
+                        self.dirty = true;
                     }
-                    "add_task" => {
-                        self.adding_task_to_project = Some(project_id);
-                        // Initialize the text field for this project if it doesn't exist
-                        self.right_click_task_text.entry(project_id).or_default();
+                    let mut days = self.stale_after_days.unwrap_or(STALE_AFTER_DAYS);
+                    let response =
+                        ui.add_enabled(warn_stale, egui::DragValue::new(&mut days).range(1..=365));
+                    ui.label("days");
+                    if response.changed() {
+                        self.stale_after_days = Some(days);
+                        self.dirty = true;
                     }
-                    "create_task" => {
-                        self.add_task_to_project(project_id, text);
+                });
+                ui.separator();
+
+                ui.label(
+                    egui::RichText::new(t(self.lang, Tr::Trash))
+                        .size(LABEL_SIZE)
+                        .strong(),
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Delete trashed items after");
+                    // Only a finished drag or edit counts: purging at every value passed
+                    // on the way would delete entries for good
+                    let mut days = self
+                        .trash_retention_draft
+                        .unwrap_or(self.trash_retention_days);
+                    let response = ui.add(egui::DragValue::new(&mut days).range(0..=365));
+                    if response.changed() {
+                        self.trash_retention_draft = Some(days);
                     }
-                    "cancel_add_task" => {
-                        self.adding_task_to_project = None;
-                        if let Some(task_text) = self.right_click_task_text.get_mut(&project_id) {
-                            task_text.clear();
+                    if response.drag_stopped() || response.lost_focus() {
+                        if let Some(days) = self.trash_retention_draft.take() {
+                            self.set_trash_retention(days);
                         }
                     }
-                    _ => {}
+                    ui.label("days");
+                })
+                .response
+                .on_hover_text("0 keeps them until the trash is full");
+                ui.separator();
+
+                ui.label(
+                    egui::RichText::new(t(self.lang, Tr::Feedback))
+                        .size(LABEL_SIZE)
+                        .strong(),
+                );
+                if ui
+                    .checkbox(
+                        &mut self.completion_feedback,
+                        "Flash tasks when they are completed",
+                    )
+                    .changed()
+                {
+                    self.dirty = true;
                 }
-            }
+                ui.separator();
 
-            // Process task actions
-            for (action, project_id, task_id, text) in task_actions {
-                match action {
-                    "start_edit" => {
-                        self.editing_task = Some((project_id, task_id));
-                        self.edit_task_text = text;
-                    }
-                    "stop_edit" => {
-                        self.editing_task = None;
-                    }
-                    _ => {}
+                ui.label(
+                    egui::RichText::new(t(self.lang, Tr::DangerZone))
+                        .size(LABEL_SIZE)
+                        .strong(),
+                );
+                ui.label(
+                    egui::RichText::new(
+                        "Remove every project, task, trash entry and note. This cannot be undone.",
+                    )
+                    .color(egui::Color32::GRAY),
+                );
+                if ui
+                    .button(
+                        egui::RichText::new(format!(
+                            "{} Clear all data...",
+                            icons::icons::ICON_DELETE_FOREVER
+                        ))
+                        .color(egui::Color32::RED),
+                    )
+                    .clicked()
+                {
+                    self.clear_confirm_text = Some(String::new());
                 }
-            }
+            });
 
-            // Remove projects
-            for &idx in projects_to_remove.iter().rev() {
-                let project_id = self.projects[idx].id;
-                self.projects.remove(idx);
-                self.new_task_texts.remove(&project_id);
-            }
-        });
+        self.show_settings = open;
     }
 
-    fn render_notes_canvas(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let rect = ui.max_rect();
+    fn render_shortcuts_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_shortcuts;
 
-            // Detect middle mouse or two-finger drag for panning
-            let canvas_response = ui.interact(
-                rect,
-                egui::Id::new("canvas_interact"),
-                egui::Sense::click_and_drag(),
-            );
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts_grid")
+                    .num_columns(2)
+                    .spacing([24.0, 6.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (keys, action) in SHORTCUTS {
+                            ui.label(egui::RichText::new(keys).monospace().strong());
+                            ui.label(action);
+                            ui.end_row();
+                        }
+                    });
+            });
 
-            // Pan with middle mouse button
-            if canvas_response.dragged_by(egui::PointerButton::Middle) {
-                self.notes_canvas.scene_rect = self
-                    .notes_canvas
-                    .scene_rect
-                    .translate(canvas_response.drag_delta());
-            }
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
+        {
+            open = false;
+        }
+        self.show_shortcuts = open;
+    }
 
-            // Pan with two-finger trackpad drag
-            // translation_delta() provides panning from scrolling or pan gestures
-            let pan_delta = ui.input(|i| i.translation_delta());
-            if pan_delta != egui::Vec2::ZERO {
-                self.notes_canvas.scene_rect = self.notes_canvas.scene_rect.translate(pan_delta);
-            }
+    fn render_clear_all_dialog(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        egui::Window::new("Clear all data?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                let Some(text) = &mut self.clear_confirm_text else {
+                    return;
+                };
+                ui.label("All projects, tasks, trash and notes will be deleted.");
+                ui.label("Type DELETE to confirm:");
+                ui.text_edit_singleline(text);
+                let typed = text.trim() == "DELETE";
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            typed,
+                            egui::Button::new(
+                                egui::RichText::new("Clear all").color(egui::Color32::RED),
+                            ),
+                        )
+                        .clicked()
+                    {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            self.clear_all_data();
+            // Written out right away so the old data can't come back on restart
+            self.persist(frame.storage_mut());
+        } else if cancelled {
+            self.clear_confirm_text = None;
+        }
+    }
+
+    /// Back to a fresh app, keeping only preferences.
+    fn clear_all_data(&mut self) {
+        *self = Self {
+            storage_locked: self.storage_locked,
+            fuzzy_search: self.fuzzy_search,
+            lang: self.lang,
+            accent: self.accent,
+            accordion_mode: self.accordion_mode,
+            new_projects_expanded: self.new_projects_expanded,
+            smart_order: self.smart_order,
+            rapid_task_entry: self.rapid_task_entry,
+            stale_after_days: self.stale_after_days,
+            trash_retention_days: self.trash_retention_days,
+            completion_feedback: self.completion_feedback,
+            show_project_panel: self.show_project_panel,
+            project_panel_width: self.project_panel_width,
+            ..Default::default()
+        };
+    }
+
+    fn handle_project_shortcuts(&mut self, ctx: &egui::Context) {
+        // Leave keys alone while the user is typing
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let has_focus = self.focused_project.is_some();
+        let (up, down, toggle, delete, undo, new_task) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::E)
+                    || (has_focus && i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Delete),
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z),
+                has_focus && i.consume_key(egui::Modifiers::NONE, egui::Key::N),
+            )
+        });
+        if new_task {
+            // Keep the typed "n" out of the entry that gets focused this frame
+            ctx.input_mut(|i| {
+                i.events
+                    .retain(|e| !matches!(e, egui::Event::Text(t) if t.eq_ignore_ascii_case("n")))
+            });
+        }
+
+        // Delete: remove the selected task (Ctrl+Z brings it back)
+        if delete && !self.presentation_mode {
+            if let Some((project_id, task_id)) = self.selected_task.take() {
+                self.apply_task_action("delete", project_id, task_id, String::new());
+            }
+        }
+
+        if undo && !self.presentation_mode {
+            self.undo();
+        }
+
+        if up || down {
+            let ids: Vec<usize> = self
+                .project_display_order()
+                .into_iter()
+                .map(|idx| self.projects[idx].id)
+                .collect();
+            let current = self
+                .focused_project
+                .and_then(|id| ids.iter().position(|&p| p == id));
+            let next = match (current, down) {
+                (None, true) => ids.first(),
+                (None, false) => ids.last(),
+                (Some(pos), true) => ids.get((pos + 1).min(ids.len() - 1)),
+                (Some(pos), false) => ids.get(pos.saturating_sub(1)),
+            };
+            if let Some(&id) = next {
+                self.focused_project = Some(id);
+                self.scroll_to_project = Some(id);
+            }
+        }
+
+        if toggle {
+            if let Some(project_id) = self.focused_project {
+                self.apply_project_action("toggle_expanded", project_id, String::new());
+            }
+        }
+
+        // N: open the inline "New Task" entry of the focused, expanded project
+        if new_task && !self.presentation_mode {
+            let project = self
+                .projects
+                .iter()
+                .find(|p| Some(p.id) == self.focused_project);
+            if let Some(project) = project.filter(|p| p.expanded && !p.at_wip_limit()) {
+                self.apply_project_action("add_task", project.id, String::new());
+            }
+        }
+
+        // Ctrl+1..9: jump to the Nth project as listed (pinned ones first)
+        const DIGIT_KEYS: [egui::Key; 9] = [
+            egui::Key::Num1,
+            egui::Key::Num2,
+            egui::Key::Num3,
+            egui::Key::Num4,
+            egui::Key::Num5,
+            egui::Key::Num6,
+            egui::Key::Num7,
+            egui::Key::Num8,
+            egui::Key::Num9,
+        ];
+        let jump = ctx.input_mut(|i| {
+            DIGIT_KEYS
+                .iter()
+                .position(|&key| i.consume_key(egui::Modifiers::COMMAND, key))
+        });
+        if let Some(project_idx) = jump.and_then(|n| self.project_display_order().get(n).copied()) {
+            self.jump_to_project(project_idx);
+        }
+    }
+
+    fn render_trash_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_trash;
+        let mut actions: Vec<(&str, usize)> = Vec::new(); // (action, trash index)
+
+        egui::Window::new(t(self.lang, Tr::Trash))
+            .id(egui::Id::new("trash_window"))
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                if self.trash.is_empty() {
+                    ui.label(egui::RichText::new("Trash is empty").color(egui::Color32::GRAY));
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        // Newest first
+                        for (idx, item) in self.trash.iter().enumerate().rev() {
+                            ui.horizontal(|ui| {
+                                let description = match item {
+                                    TrashItem::Task {
+                                        project_name, task, ..
+                                    } => format!("{} (in {})", task.text, project_name),
+                                    TrashItem::Project { project, .. } => format!(
+                                        "{} {} ({} tasks)",
+                                        icons::icons::ICON_FOLDER,
+                                        project.name,
+                                        project.tasks.len()
+                                    ),
+                                };
+                                let response = ui.label(description);
+                                if let Some(deleted_at) = item.deleted_at() {
+                                    response.on_hover_text(format!(
+                                        "Deleted {}",
+                                        format_time_since(deleted_at, Local::now())
+                                    ));
+                                }
+
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui
+                                            .button(icons::icons::ICON_DELETE_FOREVER)
+                                            .on_hover_text("Delete permanently")
+                                            .clicked()
+                                        {
+                                            actions.push(("purge", idx));
+                                        }
+                                        if ui
+                                            .button(icons::icons::ICON_RESTORE_FROM_TRASH)
+                                            .on_hover_text("Restore")
+                                            .clicked()
+                                        {
+                                            actions.push(("restore", idx));
+                                        }
+                                    },
+                                );
+                            });
+                        }
+                    });
+
+                ui.separator();
+                if ui.button("Empty trash").clicked() {
+                    actions.push(("empty", 0));
+                }
+            });
+
+        // Process actions (at most one per frame is possible from clicks)
+        for (action, idx) in actions {
+            match action {
+                "restore" => self.restore_from_trash(idx),
+                "purge" => {
+                    self.trash.remove(idx);
+                    self.dirty = true;
+                }
+                "empty" => {
+                    self.trash.clear();
+                    self.dirty = true;
+                }
+                _ => {}
+            }
+        }
+
+        self.show_trash = open;
+    }
+
+    fn render_paste_window(&mut self, ctx: &egui::Context) {
+        let Some(project_id) = self.paste_target else {
+            return;
+        };
+        let Some((project_name, open_slots)) = self
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .map(|p| (p.name.clone(), p.open_slots()))
+        else {
+            // The project went away while the window was open
+            self.paste_target = None;
+            return;
+        };
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        egui::Window::new(format!("Paste tasks into {}", project_name))
+            .id(egui::Id::new("paste_tasks_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.label("One task per line. Blank lines are skipped.");
+                ui.label(
+                    egui::RichText::new("Checklist lines like \"- [x] Done\" keep their state.")
+                        .color(egui::Color32::GRAY),
+                );
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.paste_text)
+                        .desired_rows(8)
+                        .desired_width(f32::INFINITY),
+                );
+
+                let (tasks, skipped) =
+                    fit_to_open_slots(parse_pasted_tasks(&self.paste_text), open_slots);
+                let count = tasks.len();
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Will add {} task{}",
+                        count,
+                        if count == 1 { "" } else { "s" }
+                    ))
+                    .color(egui::Color32::GRAY),
+                );
+                if skipped > 0 {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} open task{} skipped: the project's WIP limit is reached",
+                            skipped,
+                            if skipped == 1 { "" } else { "s" }
+                        ))
+                        .color(egui::Color32::from_rgb(230, 150, 0)),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(count > 0, egui::Button::new("Add tasks"))
+                        .clicked()
+                    {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            let (tasks, _) = fit_to_open_slots(parse_pasted_tasks(&self.paste_text), open_slots);
+            for (text, completed) in tasks {
+                let task_id = self.add_task_to_project(project_id, text);
+                if let Some(task) = task_id.and_then(|id| self.find_task_mut(project_id, id)) {
+                    task.set_completed(completed);
+                }
+            }
+        }
+        if confirmed || cancelled || !open {
+            self.paste_target = None;
+            self.paste_text.clear();
+        }
+    }
+
+    fn render_stats_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_stats;
+
+        egui::Window::new(t(self.lang, Tr::Stats))
+            .id(egui::Id::new("stats_window"))
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                // Only computed while the window is open
+                let today = Local::now().date_naive();
+                let all_tasks = || self.projects.iter().flat_map(|p| &p.tasks);
+                let total = all_tasks().count();
+                let completed = all_tasks().filter(|t| t.completed).count();
+                let completed_today = all_tasks()
+                    .filter(|t| t.completed)
+                    .filter_map(|t| t.completed_at)
+                    .filter(|at| at.date_naive() == today)
+                    .count();
+                let busiest = self
+                    .projects
+                    .iter()
+                    .filter(|p| p.open_task_count() > 0)
+                    .max_by_key(|p| p.open_task_count());
+
+                egui::Grid::new("stats_grid")
+                    .num_columns(2)
+                    .spacing([24.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Total tasks");
+                        ui.label(total.to_string());
+                        ui.end_row();
+
+                        ui.label("Completed");
+                        ui.label(completed.to_string());
+                        ui.end_row();
+
+                        ui.label("Completed today");
+                        ui.label(completed_today.to_string());
+                        ui.end_row();
+
+                        ui.label("Busiest project");
+                        ui.label(busiest.map_or("-".to_string(), |p| {
+                            format!("{} ({} open)", p.name, p.open_task_count())
+                        }));
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                let rate = if total == 0 {
+                    0.0
+                } else {
+                    completed as f32 / total as f32
+                };
+                ui.label("Completion rate");
+                ui.add(egui::ProgressBar::new(rate).show_percentage());
+
+                if !self.projects.is_empty() {
+                    ui.separator();
+                    ui.label(egui::RichText::new("Per project").strong());
+                    for project in &self.projects {
+                        let total = project.tasks.len();
+                        let done = total - project.open_task_count();
+                        let fraction = if total == 0 {
+                            0.0
+                        } else {
+                            done as f32 / total as f32
+                        };
+                        ui.horizontal(|ui| {
+                            ui.add_sized([110.0, 18.0], egui::Label::new(&project.name).truncate());
+                            ui.add(
+                                egui::ProgressBar::new(fraction)
+                                    .text(format!("{}/{}", done, total)),
+                            );
+                        });
+                    }
+                }
+            });
+
+        self.show_stats = open;
+    }
+
+    fn render_todo_view(&mut self, ctx: &egui::Context) {
+        let lang = self.lang;
+        if self.show_project_panel {
+            self.render_project_panel(ctx);
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if !self.show_project_panel
+                    && ui
+                        .button(egui::RichText::new(icons::icons::ICON_MENU).size(BUTTON_SIZE))
+                        .on_hover_text("Show project list")
+                        .clicked()
+                {
+                    self.show_project_panel = true;
+                }
+
+                // Left side - Username
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{}: {}",
+                        t(lang, Tr::User),
+                        whoami::username().unwrap_or_else(|_| "Unknown".to_string())
+                    ))
+                    .size(LABEL_SIZE),
+                );
+
+                // Get the remaining width for the rest of the layout
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    // Right side - Current date with padding (from right to left)
+                    ui.add_space(10.0); // Padding from right edge
+                    let now = Local::now();
+                    ui.label(
+                        egui::RichText::new(format!("{}", now.format("%d/%m/%Y"))).size(LABEL_SIZE),
+                    );
+
+                    // Presentation mode: a read-only view for sharing the screen
+                    let editing = self.editing_project.is_some() || self.editing_task.is_some();
+                    let lock_icon = if self.presentation_mode {
+                        icons::icons::ICON_LOCK
+                    } else {
+                        icons::icons::ICON_LOCK_OPEN
+                    };
+                    if ui
+                        .add_enabled(
+                            self.presentation_mode || !editing,
+                            egui::Button::selectable(
+                                self.presentation_mode,
+                                egui::RichText::new(lock_icon).size(BUTTON_SIZE),
+                            ),
+                        )
+                        .on_hover_text(if self.presentation_mode {
+                            "Presentation mode is on (read-only) - click to edit again"
+                        } else {
+                            "Presentation mode (read-only)"
+                        })
+                        .on_disabled_hover_text("Finish editing first")
+                        .clicked()
+                    {
+                        self.presentation_mode = !self.presentation_mode;
+                        self.selected_task = None;
+                        if self.presentation_mode {
+                            // These windows only exist to change things
+                            self.show_settings = false;
+                            self.show_trash = false;
+                            self.paste_target = None;
+                            self.pending_import = None;
+                            self.show_notes = false;
+                        }
+                    }
+
+                    // Center the title in remaining space
+                    ui.with_layout(
+                        egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
+                        |ui| {
+                            let mut title =
+                                egui::RichText::new(t(lang, Tr::AppTitle)).size(HEADING_SIZE);
+                            if let Some([r, g, b]) = self.accent {
+                                title = title.color(egui::Color32::from_rgb(r, g, b));
+                            }
+                            ui.heading(title);
+                        },
+                    );
+                });
+            });
+            ui.separator();
+
+            // Add new project section (plus the toolbar)
+            ui.horizontal(|ui| {
+                if !self.presentation_mode {
+                    ui.label(egui::RichText::new(t(lang, Tr::NewProject)).size(LABEL_SIZE));
+                    let response = ui.text_edit_singleline(&mut self.new_project_name);
+                    if response.changed() {
+                        self.project_name_error = None;
+                    }
+
+                    if ui
+                        .button(
+                            egui::RichText::new(format!(
+                                "{} {}",
+                                icons::icons::ICON_ADD,
+                                t(lang, Tr::AddProject)
+                            ))
+                            .size(BUTTON_SIZE),
+                        )
+                        .clicked()
+                        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                    {
+                        if self.new_project_name.trim().is_empty() {
+                            self.warn_blank(ui, BlankInput::ProjectName);
+                        } else {
+                            self.add_project();
+                        }
+                    }
+                    self.render_blank_warning(ui, BlankInput::ProjectName, "Name can't be empty");
+
+                    if let Some(error) = &self.project_name_error {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(230, 150, 0),
+                            format!("{} {}", icons::icons::ICON_WARNING, error),
+                        );
+                    }
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if !self.presentation_mode {
+                        if ui
+                            .button(
+                                egui::RichText::new(icons::icons::ICON_SETTINGS).size(BUTTON_SIZE),
+                            )
+                            .on_hover_text(t(lang, Tr::Settings))
+                            .clicked()
+                        {
+                            self.show_settings = !self.show_settings;
+                        }
+
+                        if ui
+                            .button(
+                                egui::RichText::new(format!(
+                                    "{} {} ({})",
+                                    icons::icons::ICON_DELETE,
+                                    t(lang, Tr::Trash),
+                                    self.trash.len()
+                                ))
+                                .size(BUTTON_SIZE),
+                            )
+                            .clicked()
+                        {
+                            self.show_trash = !self.show_trash;
+                        }
+                    }
+
+                    if ui
+                        .button(
+                            egui::RichText::new(format!(
+                                "{} {}",
+                                icons::icons::ICON_BAR_CHART,
+                                t(lang, Tr::Stats)
+                            ))
+                            .size(BUTTON_SIZE),
+                        )
+                        .clicked()
+                    {
+                        self.show_stats = !self.show_stats;
+                    }
+
+                    if ui
+                        .button(
+                            egui::RichText::new(format!(
+                                "{} {}",
+                                icons::icons::ICON_DOWNLOAD,
+                                t(lang, Tr::ExportCsv)
+                            ))
+                            .size(BUTTON_SIZE),
+                        )
+                        .clicked()
+                    {
+                        self.export_csv();
+                    }
+
+                    if ui
+                        .button(
+                            egui::RichText::new(format!(
+                                "{} {}",
+                                icons::icons::ICON_DOWNLOAD,
+                                t(lang, Tr::ExportJson)
+                            ))
+                            .size(BUTTON_SIZE),
+                        )
+                        .clicked()
+                    {
+                        self.export_json();
+                    }
+
+                    if !self.presentation_mode
+                        && ui
+                            .button(
+                                egui::RichText::new(format!(
+                                    "{} {}",
+                                    icons::icons::ICON_UPLOAD,
+                                    t(lang, Tr::Import)
+                                ))
+                                .size(BUTTON_SIZE),
+                            )
+                            .clicked()
+                    {
+                        self.import_json();
+                    }
+                });
+            });
+
+            // Quick add a task to any project
+            if !self.presentation_mode {
+                self.render_quick_add_bar(ui);
+            }
+
+            // Task search
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(t(lang, Tr::Search)).size(LABEL_SIZE));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.search_query).hint_text(t(
+                        lang,
+                        if self.fuzzy_search {
+                            Tr::FuzzyHint
+                        } else {
+                            Tr::FilterTasksHint
+                        },
+                    )),
+                );
+                if !self.search_query.is_empty() && ui.button(icons::icons::ICON_CLOSE).clicked() {
+                    self.search_query.clear();
+                }
+                ui.checkbox(&mut self.fuzzy_search, t(lang, Tr::Fuzzy))
+                    .on_hover_text("Match letters in order, even with gaps between them");
+                egui::ComboBox::from_id_salt("status_filter")
+                    .selected_text(self.status_filter.label(lang))
+                    .show_ui(ui, |ui| {
+                        for filter in StatusFilter::ALL {
+                            if ui
+                                .selectable_value(
+                                    &mut self.status_filter,
+                                    filter,
+                                    filter.label(lang),
+                                )
+                                .changed()
+                            {
+                                self.dirty = true;
+                            }
+                        }
+                    });
+                ui.checkbox(&mut self.overdue_only, t(lang, Tr::OverdueOnly));
+                ui.checkbox(&mut self.flagged_only, t(lang, Tr::FlaggedOnly));
+                ui.checkbox(&mut self.show_snoozed, t(lang, Tr::ShowSnoozed));
+            });
+
+            self.render_filter_chips(ui);
+
+            ui.add_space(8.0);
+
+            // Project list or a flat list of what's due soon
+            ui.horizontal(|ui| {
+                ui.selectable_value(
+                    &mut self.focus_view,
+                    None,
+                    egui::RichText::new(format!(
+                        "{} {}",
+                        icons::icons::ICON_VIEW_LIST,
+                        t(lang, Tr::ProjectsView)
+                    ))
+                    .size(BUTTON_SIZE),
+                );
+                ui.selectable_value(
+                    &mut self.focus_view,
+                    Some(FocusRange::Today),
+                    egui::RichText::new(format!(
+                        "{} {}",
+                        icons::icons::ICON_TODAY,
+                        t(lang, Tr::DueToday)
+                    ))
+                    .size(BUTTON_SIZE),
+                );
+                ui.selectable_value(
+                    &mut self.focus_view,
+                    Some(FocusRange::Week),
+                    egui::RichText::new(format!(
+                        "{} {}",
+                        icons::icons::ICON_DATE_RANGE,
+                        t(lang, Tr::DueThisWeek)
+                    ))
+                    .size(BUTTON_SIZE),
+                );
+
+                ui.separator();
+                if ui
+                    .toggle_value(
+                        &mut self.smart_order,
+                        egui::RichText::new(format!(
+                            "{} {}",
+                            icons::icons::ICON_SORT,
+                            t(lang, Tr::SmartOrder)
+                        ))
+                        .size(BUTTON_SIZE),
+                    )
+                    .on_hover_text(
+                        "Open tasks first, by due date and then flagged first, in every project. \
+                         Overrides each project's own sort; the stored order is unchanged.",
+                    )
+                    .changed()
+                {
+                    self.dirty = true;
+                }
+            });
+
+            ui.add_space(8.0);
+
+            // Simplified instruction for users (nothing to add while read-only)
+            if !self.presentation_mode {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(t(lang, Tr::RightClickHint).replace(
+                            "{icons}",
+                            &format!(
+                                "{}/{}",
+                                icons::icons::ICON_CHEVRON_RIGHT,
+                                icons::icons::ICON_EXPAND_MORE
+                            ),
+                        ))
+                        .size(LABEL_SIZE)
+                        .color(egui::Color32::GRAY),
+                    );
+                });
+            }
+
+            ui.add_space(16.0);
+
+            ui.separator();
+
+            // Display projects in a scroll area
+            let mut project_actions = Vec::new(); // Store actions to perform after iteration
+            let mut task_actions = Vec::new(); // Store task actions
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    if let Some(range) = self.focus_view {
+                        self.render_focus_list(ui, range, &mut task_actions);
+                        return;
+                    }
+                    for project_idx in self.project_display_order() {
+                        // While filtering, hide projects without a single match
+                        if self.is_filtering()
+                            && self.visible_tasks(&self.projects[project_idx]).is_empty()
+                        {
+                            continue;
+                        }
+                        let project_id = self.projects[project_idx].id;
+                        // A project being jumped to has to be drawn to scroll to it
+                        let always = self.scroll_to_project == Some(project_id);
+                        let key = egui::Id::new(("project", project_id));
+                        let stamp = row_stamp(&(
+                            &self.projects[project_idx],
+                            self.project_search.get(&project_id),
+                        ));
+                        self.lazy_row(ui, key, stamp, always, |app, ui| {
+                            // Render from a snapshot; all changes go through the deferred actions
+                            let project = app.projects[project_idx].clone();
+                            ui.push_id(project.id, |ui| {
+                                app.render_project(
+                                    ui,
+                                    &project,
+                                    &mut project_actions,
+                                    &mut task_actions,
+                                );
+                            });
+                            ui.add_space(16.0);
+                        });
+                    }
+                });
+
+            // Process project actions
+            for (action, project_id, text) in project_actions {
+                self.apply_project_action(action, project_id, text);
+            }
+
+            // Process task actions
+            for (action, project_id, task_id, text) in task_actions {
+                self.apply_task_action(action, project_id, task_id, text);
+            }
+        });
+    }
+
+    /// Project names only; clicking one expands it and scrolls it into view.
+    fn render_project_panel(&mut self, ctx: &egui::Context) {
+        let mut jump_to = None;
+
+        let response = egui::SidePanel::left("project_panel")
+            .resizable(true)
+            .default_width(self.project_panel_width)
+            .width_range(120.0..=400.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(t(self.lang, Tr::ProjectsView))
+                            .size(LABEL_SIZE)
+                            .strong(),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .button(
+                                egui::RichText::new(icons::icons::ICON_MENU_OPEN).size(BUTTON_SIZE),
+                            )
+                            .on_hover_text("Hide project list")
+                            .clicked()
+                        {
+                            self.show_project_panel = false;
+                        }
+                    });
+                });
+                ui.separator();
+
+                if self.projects.is_empty() {
+                    ui.label(egui::RichText::new("No projects yet").color(egui::Color32::GRAY));
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for project_idx in self.project_display_order() {
+                            let project = &self.projects[project_idx];
+                            let mut label = project.name.clone();
+                            if project.pinned {
+                                label = format!("{} {}", icons::icons::ICON_PUSH_PIN, label);
+                            }
+                            let selected = self.focused_project == Some(project.id);
+                            if ui
+                                .add(
+                                    egui::Button::selectable(
+                                        selected,
+                                        egui::RichText::new(label).size(LABEL_SIZE),
+                                    )
+                                    .truncate(),
+                                )
+                                .clicked()
+                            {
+                                jump_to = Some(project_idx);
+                            }
+                        }
+                    });
+            });
+
+        // Remember a resized width across restarts
+        let width = response.response.rect.width();
+        if (width - self.project_panel_width).abs() > 0.5 {
+            self.project_panel_width = width;
+            self.dirty = true;
+        }
+
+        if let Some(project_idx) = jump_to {
+            self.jump_to_project(project_idx);
+        }
+    }
+
+    /// Expands the project at `project_idx`, focuses it and scrolls it into view.
+    fn jump_to_project(&mut self, project_idx: usize) {
+        let project_id = self.projects[project_idx].id;
+        self.expand_project(project_id);
+        self.focused_project = Some(project_id);
+        self.scroll_to_project = Some(project_id);
+    }
+
+    /// Expands a project; in accordion mode every other project is collapsed.
+    fn expand_project(&mut self, project_id: usize) {
+        for project in &mut self.projects {
+            let expanded = project.id == project_id || (project.expanded && !self.accordion_mode);
+            if project.expanded != expanded {
+                project.expanded = expanded;
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// One extra native window per detached project, editing the shared state.
+    /// Backends without multi-window support get an in-app window instead.
+    fn render_detached_projects(&mut self, ctx: &egui::Context) {
+        let project_ids: Vec<usize> = self.detached_projects.iter().copied().collect();
+        for project_id in project_ids {
+            let Some(mut project) = self.projects.iter().find(|p| p.id == project_id).cloned()
+            else {
+                // Deleted, or replaced by an import
+                self.detached_projects.remove(&project_id);
+                continue;
+            };
+            // The window is there to show the tasks, whatever the main list does
+            project.expanded = true;
+
+            let mut project_actions = Vec::new();
+            let mut task_actions = Vec::new();
+            let mut closed = false;
+
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of(("detached_project", project_id)),
+                egui::ViewportBuilder::default()
+                    .with_title(format!("{} - {}", project.name, APP_NAME))
+                    .with_inner_size([480.0, 560.0]),
+                |ctx, class| {
+                    let mut show = |ui: &mut egui::Ui| {
+                        egui::ScrollArea::vertical()
+                            .auto_shrink([false, true])
+                            .show(ui, |ui| {
+                                self.render_project(
+                                    ui,
+                                    &project,
+                                    &mut project_actions,
+                                    &mut task_actions,
+                                );
+                            });
+                    };
+
+                    if class == egui::ViewportClass::Embedded {
+                        let mut open = true;
+                        egui::Window::new(&project.name)
+                            .id(egui::Id::new(("detached_project", project_id)))
+                            .open(&mut open)
+                            .default_width(420.0)
+                            .show(ctx, |ui| show(ui));
+                        closed = !open;
+                    } else {
+                        egui::CentralPanel::default().show(ctx, |ui| show(ui));
+                        closed = ctx.input(|i| i.viewport().close_requested());
+                    }
+                },
+            );
+
+            for (action, project_id, text) in project_actions {
+                self.apply_project_action(action, project_id, text);
+            }
+            for (action, project_id, task_id, text) in task_actions {
+                self.apply_task_action(action, project_id, task_id, text);
+            }
+            if closed {
+                self.detached_projects.remove(&project_id);
+            }
+        }
+    }
+
+    /// Tasks due in `range` across all projects, grouped by due date. Open tasks
+    /// from earlier days are included as overdue.
+    fn render_focus_list(
+        &mut self,
+        ui: &mut egui::Ui,
+        range: FocusRange,
+        task_actions: &mut Vec<TaskAction>,
+    ) {
+        let today = Local::now().date_naive();
+        let last_day = match range {
+            FocusRange::Today => today,
+            FocusRange::Week => today + chrono::Duration::days(6),
+        };
+
+        // Snapshots, like the project view; changes go through the deferred actions
+        let mut due: Vec<(usize, String, Task)> = Vec::new(); // (project_id, project name, task)
+        for project in &self.projects {
+            for task in self.visible_tasks(project) {
+                let Some(date) = task.due_date else {
+                    continue;
+                };
+                if date <= last_day && (date >= today || !task.completed) {
+                    due.push((project.id, project.name.clone(), task.clone()));
+                }
+            }
+        }
+        // By date, then timed before untimed; stable, so ties keep their project order
+        due.sort_by_key(|(_, _, task)| (task.due_date, task.due_time.is_none(), task.due_time));
+
+        if due.is_empty() {
+            ui.label(
+                egui::RichText::new(t(
+                    self.lang,
+                    match range {
+                        FocusRange::Today => Tr::NothingDueToday,
+                        FocusRange::Week => Tr::NothingDueThisWeek,
+                    },
+                ))
+                .size(LABEL_SIZE)
+                .color(egui::Color32::GRAY),
+            );
+            return;
+        }
+
+        let mut current_date = None;
+        let mut number = 0;
+        for (project_id, project_name, task) in &due {
+            if current_date != task.due_date {
+                current_date = task.due_date;
+                number = 0;
+                let date = task.due_date.unwrap_or(today);
+                let heading = if date < today {
+                    t(self.lang, Tr::OverdueDate)
+                        .replace("{date}", &date.format("%a %d/%m/%Y").to_string())
+                } else if date == today {
+                    t(self.lang, Tr::Today).to_string()
+                } else if Some(date) == today.succ_opt() {
+                    t(self.lang, Tr::Tomorrow).to_string()
+                } else {
+                    date.format("%A %d/%m/%Y").to_string()
+                };
+                ui.add_space(12.0);
+                ui.label(egui::RichText::new(heading).size(PROJECT_TITLE_SIZE));
+                ui.separator();
+            }
+
+            number += 1;
+            let key = egui::Id::new(("focus_task", task.id));
+            self.lazy_row(ui, key, row_stamp(task), false, |app, ui| {
+                ui.add_space(8.0);
+                ui.label(
+                    egui::RichText::new(format!("{} {}", icons::icons::ICON_FOLDER, project_name))
+                        .size(BUTTON_SIZE)
+                        .color(egui::Color32::GRAY),
+                );
+                ui.push_id(task.id, |ui| {
+                    app.render_task_row(ui, *project_id, task, number, task_actions);
+                });
+            });
+        }
+    }
+
+    fn render_quick_add_bar(&mut self, ui: &mut egui::Ui) {
+        // Fall back to the first project if the selection is missing or was deleted
+        if !self
+            .quick_add_project
+            .is_some_and(|id| self.projects.iter().any(|p| p.id == id))
+        {
+            self.quick_add_project = self.projects.first().map(|p| p.id);
+        }
+
+        let lang = self.lang;
+        let has_projects = !self.projects.is_empty();
+        ui.add_enabled_ui(has_projects, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(t(lang, Tr::QuickTask)).size(LABEL_SIZE));
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.quick_add_text).hint_text(t(
+                        lang,
+                        if has_projects {
+                            Tr::TaskTextHint
+                        } else {
+                            Tr::CreateProjectFirst
+                        },
+                    )),
+                );
+
+                let selected = self
+                    .projects
+                    .iter()
+                    .find(|p| Some(p.id) == self.quick_add_project);
+                let at_limit = selected.is_some_and(|p| p.at_wip_limit());
+                egui::ComboBox::from_id_salt("quick_add_project")
+                    .selected_text(selected.map_or("", |p| p.name.as_str()))
+                    .show_ui(ui, |ui| {
+                        for project in &self.projects {
+                            ui.selectable_value(
+                                &mut self.quick_add_project,
+                                Some(project.id),
+                                &project.name,
+                            );
+                        }
+                    });
+
+                let add_clicked = ui
+                    .add_enabled(
+                        !at_limit,
+                        egui::Button::new(
+                            egui::RichText::new(format!(
+                                "{} {}",
+                                icons::icons::ICON_ADD,
+                                t(lang, Tr::Add)
+                            ))
+                            .size(BUTTON_SIZE),
+                        ),
+                    )
+                    .on_disabled_hover_text("WIP limit reached for this project")
+                    .clicked();
+                let enter_pressed =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                if (add_clicked || enter_pressed) && !at_limit {
+                    if self.quick_add_text.trim().is_empty() {
+                        self.warn_blank(ui, BlankInput::QuickAdd);
+                    } else if let Some(project_id) = self.quick_add_project {
+                        let text = std::mem::take(&mut self.quick_add_text);
+                        self.add_task_to_project(project_id, text);
+                    }
+                }
+                self.render_blank_warning(ui, BlankInput::QuickAdd, "Task text can't be empty");
+            });
+        });
+    }
+
+    fn render_project(
+        &mut self,
+        ui: &mut egui::Ui,
+        project: &Project,
+        project_actions: &mut Vec<ProjectAction>,
+        task_actions: &mut Vec<TaskAction>,
+    ) {
+        let mut frame = egui::Frame::group(ui.style()).inner_margin(egui::Margin::same(16));
+        if self.focused_project == Some(project.id) {
+            frame = frame.stroke(ui.visuals().selection.stroke);
+        }
+
+        let frame_response = frame
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                // Project header
+                ui.horizontal(|ui| {
+                    // Expand/collapse button with right-click to add task
+                    let expand_icon = if project.expanded {
+                        icons::icons::ICON_EXPAND_MORE
+                    } else {
+                        icons::icons::ICON_CHEVRON_RIGHT
+                    };
+                    let expand_response =
+                        ui.button(egui::RichText::new(expand_icon).size(BUTTON_SIZE));
+
+                    if expand_response.clicked() {
+                        project_actions.push(("toggle_expanded", project.id, String::new()));
+                    }
+
+                    // Right-click on expand button to add task
+                    // (blocked once the project reaches its WIP limit)
+                    if self.presentation_mode {
+                        // Read-only: no task entry
+                    } else if project.at_wip_limit() {
+                        expand_response.on_hover_text(
+                            "WIP limit reached - complete a task before adding more",
+                        );
+                    } else if expand_response.secondary_clicked() {
+                        project_actions.push(("add_task", project.id, String::new()));
+                    }
+
+                    // Tri-state checkbox: none / some / all tasks done
+                    let done = project.tasks.len() - project.open_task_count();
+                    let all_done = !project.tasks.is_empty() && done == project.tasks.len();
+                    let check_icon = if all_done {
+                        icons::icons::ICON_CHECK_BOX
+                    } else if done > 0 {
+                        icons::icons::ICON_INDETERMINATE_CHECK_BOX
+                    } else {
+                        icons::icons::ICON_CHECK_BOX_OUTLINE_BLANK
+                    };
+                    if ui
+                        .add_enabled(
+                            !project.tasks.is_empty() && !self.presentation_mode,
+                            egui::Button::new(egui::RichText::new(check_icon).size(BUTTON_SIZE))
+                                .frame(false),
+                        )
+                        .on_hover_text(if all_done {
+                            "Mark all tasks incomplete"
+                        } else {
+                            "Mark all tasks complete"
+                        })
+                        .clicked()
+                    {
+                        let action = if all_done {
+                            "uncomplete_all"
+                        } else {
+                            "complete_all"
+                        };
+                        project_actions.push((action, project.id, String::new()));
+                    }
+
+                    // Project name and controls
+                    if self.editing_project == Some(project.id) {
+                        // Editing mode: show text input with confirmation buttons
+                        let response = ui.text_edit_singleline(&mut self.edit_project_text);
+                        if response.changed() {
+                            self.edit_project_error = None;
+                        }
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            project_actions.push(("save_edit", project.id, String::new()));
+                        } else if response.lost_focus()
+                            && ui.input(|i| i.key_pressed(egui::Key::Escape))
+                        {
+                            project_actions.push(("stop_edit", project.id, String::new()));
+                        }
+
+                        // Optional WIP limit
+                        let mut has_limit = self.edit_project_wip_limit.is_some();
+                        if ui.checkbox(&mut has_limit, "WIP limit").changed() {
+                            self.edit_project_wip_limit = has_limit.then_some(5);
+                        }
+                        if let Some(limit) = &mut self.edit_project_wip_limit {
+                            ui.add(egui::DragValue::new(limit).range(1..=99));
+                        }
+
+                        if ui.button(icons::icons::ICON_CHECK).clicked() {
+                            project_actions.push(("save_edit", project.id, String::new()));
+                        }
+                        if ui.button(icons::icons::ICON_CLOSE).clicked() {
+                            project_actions.push(("stop_edit", project.id, String::new()));
+                        }
+                        if self.project_edit_dirty() {
+                            unsaved_edit_marker(ui);
+                        }
+
+                        if let Some(error) = &self.edit_project_error {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(230, 150, 0),
+                                format!("{} {}", icons::icons::ICON_WARNING, error),
+                            );
+                        }
+                    } else {
+                        // Display mode: show label with edit button
+                        // Double-click the name to start editing
+                        if ui
+                            .add(
+                                egui::Label::new(
+                                    egui::RichText::new(&project.name).size(PROJECT_TITLE_SIZE),
+                                )
+                                .sense(egui::Sense::click()),
+                            )
+                            .double_clicked()
+                            && !self.presentation_mode
+                        {
+                            project_actions.push(("start_edit", project.id, project.name.clone()));
+                        }
+
+                        // Collapsed: the size of the project at a glance
+                        if !project.expanded {
+                            let total = project.tasks.len();
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "({} {}, {} done)",
+                                    total,
+                                    if total == 1 { "task" } else { "tasks" },
+                                    done
+                                ))
+                                .size(BUTTON_SIZE)
+                                .color(egui::Color32::GRAY),
+                            );
+                        }
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if !self.presentation_mode {
+                                // Delete project button
+                                if ui
+                                    .button(
+                                        egui::RichText::new(icons::icons::ICON_DELETE)
+                                            .size(BUTTON_SIZE),
+                                    )
+                                    .clicked()
+                                {
+                                    project_actions.push(("delete", project.id, String::new()));
+                                }
+
+                                // Bulk complete / reset, e.g. for a recurring checklist
+                                let open = project.open_task_count();
+                                if ui
+                                    .add_enabled(
+                                        open < project.tasks.len(),
+                                        egui::Button::new(
+                                            egui::RichText::new(icons::icons::ICON_REMOVE_DONE)
+                                                .size(BUTTON_SIZE),
+                                        ),
+                                    )
+                                    .on_hover_text("Mark all incomplete")
+                                    .clicked()
+                                {
+                                    project_actions.push((
+                                        "uncomplete_all",
+                                        project.id,
+                                        String::new(),
+                                    ));
+                                }
+                                if ui
+                                    .add_enabled(
+                                        open > 0,
+                                        egui::Button::new(
+                                            egui::RichText::new(icons::icons::ICON_DONE_ALL)
+                                                .size(BUTTON_SIZE),
+                                        ),
+                                    )
+                                    .on_hover_text("Mark all complete")
+                                    .clicked()
+                                {
+                                    project_actions.push((
+                                        "complete_all",
+                                        project.id,
+                                        String::new(),
+                                    ));
+                                }
+                            }
+
+                            // Export this project only
+                            if ui
+                                .button(
+                                    egui::RichText::new(icons::icons::ICON_DOWNLOAD)
+                                        .size(BUTTON_SIZE),
+                                )
+                                .on_hover_text("Export project as JSON")
+                                .clicked()
+                            {
+                                project_actions.push(("export", project.id, String::new()));
+                            }
+
+                            if !self.presentation_mode {
+                                // Bulk paste tasks button
+                                if ui
+                                    .add_enabled(
+                                        !project.at_wip_limit(),
+                                        egui::Button::new(
+                                            egui::RichText::new(icons::icons::ICON_CONTENT_PASTE)
+                                                .size(BUTTON_SIZE),
+                                        ),
+                                    )
+                                    .on_hover_text("Paste tasks")
+                                    .on_disabled_hover_text("WIP limit reached")
+                                    .clicked()
+                                {
+                                    project_actions.push((
+                                        "paste_tasks",
+                                        project.id,
+                                        String::new(),
+                                    ));
+                                }
+
+                                // Task sort menu
+                                let sort_color = if project.sort == TaskSort::Manual {
+                                    egui::Color32::GRAY
+                                } else {
+                                    ui.visuals().selection.bg_fill
+                                };
+                                ui.menu_button(
+                                    egui::RichText::new(icons::icons::ICON_SORT)
+                                        .size(BUTTON_SIZE)
+                                        .color(sort_color),
+                                    |ui| {
+                                        for (sort, label) in [
+                                            (TaskSort::Manual, "Manual order"),
+                                            (TaskSort::DueDate, "Due date"),
+                                        ] {
+                                            if ui.radio(project.sort == sort, label).clicked() {
+                                                let value = match sort {
+                                                    TaskSort::Manual => "manual",
+                                                    TaskSort::DueDate => "due_date",
+                                                };
+                                                project_actions.push((
+                                                    "set_sort",
+                                                    project.id,
+                                                    value.to_string(),
+                                                ));
+                                                ui.close();
+                                            }
+                                        }
+                                        ui.separator();
+                                        if ui
+                                            .add_enabled(
+                                                project.sort != TaskSort::Manual,
+                                                egui::Button::new("Keep as manual order"),
+                                            )
+                                            .clicked()
+                                        {
+                                            project_actions.push((
+                                                "persist_sort",
+                                                project.id,
+                                                String::new(),
+                                            ));
+                                            ui.close();
+                                        }
+                                    },
+                                )
+                                .response
+                                .on_hover_text(
+                                    if self.smart_order {
+                                        "Sort tasks (Smart order is on and takes precedence)"
+                                    } else {
+                                        "Sort tasks"
+                                    },
+                                );
+                            }
+
+                            // Pop the project out into its own window
+                            if ui
+                                .add_enabled(
+                                    !self.detached_projects.contains(&project.id),
+                                    egui::Button::new(
+                                        egui::RichText::new(icons::icons::ICON_OPEN_IN_NEW)
+                                            .size(BUTTON_SIZE),
+                                    ),
+                                )
+                                .on_hover_text("Open in a separate window")
+                                .clicked()
+                            {
+                                project_actions.push(("detach", project.id, String::new()));
+                            }
+
+                            if !self.presentation_mode {
+                                // Pin project button
+                                let pin_color = if project.pinned {
+                                    ui.visuals().selection.bg_fill
+                                } else {
+                                    egui::Color32::GRAY
+                                };
+                                if ui
+                                    .button(
+                                        egui::RichText::new(icons::icons::ICON_PUSH_PIN)
+                                            .size(BUTTON_SIZE)
+                                            .color(pin_color),
+                                    )
+                                    .on_hover_text(if project.pinned {
+                                        "Unpin"
+                                    } else {
+                                        "Pin to top"
+                                    })
+                                    .clicked()
+                                {
+                                    project_actions.push((
+                                        "toggle_pinned",
+                                        project.id,
+                                        String::new(),
+                                    ));
+                                }
+
+                                // Edit project button
+                                if ui
+                                    .button(
+                                        egui::RichText::new(icons::icons::ICON_EDIT)
+                                            .size(BUTTON_SIZE),
+                                    )
+                                    .clicked()
+                                {
+                                    project_actions.push((
+                                        "start_edit",
+                                        project.id,
+                                        project.name.clone(),
+                                    ));
+                                }
+                            }
+
+                            // Estimated time still to go
+                            let remaining = project.remaining_estimate();
+                            if remaining > 0 {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "~{} left",
+                                        format_minutes(remaining)
+                                    ))
+                                    .size(BUTTON_SIZE)
+                                    .color(egui::Color32::GRAY),
+                                )
+                                .on_hover_text("Estimated time of the open tasks");
+                            }
+
+                            // This week's activity
+                            let (added, completed) = project.week_activity(Local::now());
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "+{} / {}{} this week",
+                                    added,
+                                    icons::icons::ICON_CHECK,
+                                    completed
+                                ))
+                                .size(BUTTON_SIZE)
+                                .color(egui::Color32::GRAY),
+                            )
+                            .on_hover_text("Tasks added / completed in the last 7 days");
+
+                            // Open task count against the WIP limit
+                            if let Some(limit) = project.wip_limit {
+                                let open = project.open_task_count();
+                                let color = if open >= limit {
+                                    egui::Color32::RED
+                                } else {
+                                    egui::Color32::GRAY
+                                };
+                                ui.label(
+                                    egui::RichText::new(format!("{}/{}", open, limit))
+                                        .size(BUTTON_SIZE)
+                                        .color(color),
+                                )
+                                .on_hover_text("Open tasks / WIP limit");
+                            }
+
+                            // Search within this project only
+                            if project.expanded {
+                                let mut query = self
+                                    .project_search
+                                    .get(&project.id)
+                                    .cloned()
+                                    .unwrap_or_default();
+                                let response = ui.add(
+                                    egui::TextEdit::singleline(&mut query)
+                                        .hint_text(format!(
+                                            "{} In project",
+                                            icons::icons::ICON_SEARCH
+                                        ))
+                                        .desired_width(120.0),
+                                );
+                                if response.changed() {
+                                    if query.is_empty() {
+                                        self.project_search.remove(&project.id);
+                                    } else {
+                                        self.project_search.insert(project.id, query);
+                                    }
+                                }
+                            }
+                        });
+                    }
+                });
+
+                // Project description: editable while renaming, otherwise shown when expanded
+                if self.editing_project == Some(project.id) {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.edit_project_description)
+                            .hint_text("Description (optional)")
+                            .desired_rows(2)
+                            .desired_width(f32::INFINITY),
+                    );
+                } else if project.expanded && !project.description.is_empty() {
+                    ui.label(
+                        egui::RichText::new(&project.description)
+                            .size(BUTTON_SIZE)
+                            .color(egui::Color32::GRAY),
+                    );
+                }
+
+                // Tasks (only shown when expanded, or when filtering)
+                if project.expanded || self.is_filtering() {
+                    ui.indent("tasks", |ui| {
+                        // Active tasks first; completed ones are grouped below.
+                        // This only affects display, not the stored order.
+                        // A task that was just checked off stays put until its flash ends.
+                        let now = ui.input(|i| i.time);
+                        let (completed_tasks, active_tasks): (Vec<&Task>, Vec<&Task>) =
+                            self.visible_tasks(project).into_iter().partition(|t| {
+                                t.completed && self.completion_flash_progress(t.id, now).is_none()
+                            });
+
+                        // Nothing to show yet: point at the ways to add a task
+                        if project.tasks.is_empty()
+                            && self.adding_task_to_project != Some(project.id)
+                        {
+                            ui.add_space(8.0);
+                            ui.label(
+                                egui::RichText::new(t(
+                                    self.lang,
+                                    if self.presentation_mode {
+                                        Tr::NoTasksYet
+                                    } else {
+                                        Tr::NoTasksYetHint
+                                    },
+                                ))
+                                .size(BUTTON_SIZE)
+                                .color(egui::Color32::GRAY),
+                            );
+                        }
+
+                        // Numbers follow the displayed order, continuing into the completed group
+                        let active_count = active_tasks.len();
+                        for (task_idx, task) in active_tasks.into_iter().enumerate() {
+                            let key = egui::Id::new(("task", task.id));
+                            self.lazy_row(ui, key, row_stamp(task), false, |app, ui| {
+                                ui.add_space(8.0);
+                                app.render_task_row(
+                                    ui,
+                                    project.id,
+                                    task,
+                                    task_idx + 1,
+                                    task_actions,
+                                );
+                            });
+                        }
+
+                        // Show inline task creation UI when this project is selected for task addition
+                        if self.adding_task_to_project == Some(project.id)
+                            && !self.presentation_mode
+                        {
+                            ui.add_space(8.0);
+                            ui.horizontal(|ui| {
+                                ui.label(t(self.lang, Tr::NewTask));
+                                let task_text =
+                                    self.right_click_task_text.get_mut(&project.id).unwrap();
+                                let response = ui.text_edit_singleline(task_text);
+                                let is_blank = task_text.trim().is_empty();
+                                if self.focus_add_task_field {
+                                    response.request_focus();
+                                    self.focus_add_task_field = false;
+                                }
+
+                                // The entry stays open until an explicit confirm or cancel:
+                                // merely losing focus (e.g. clicking elsewhere) keeps the draft.
+                                let enter_pressed = response.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                let escape_pressed = response.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Escape));
+
+                                let confirm_clicked = ui.button(icons::icons::ICON_CHECK).clicked();
+                                let close_clicked = ui.button(icons::icons::ICON_CLOSE).clicked();
+
+                                if (confirm_clicked || enter_pressed) && is_blank {
+                                    self.warn_blank(ui, BlankInput::NewTask(project.id));
+                                } else if confirm_clicked || enter_pressed {
+                                    project_actions.push((
+                                        "submit_task",
+                                        project.id,
+                                        String::new(),
+                                    ));
+                                } else if close_clicked || escape_pressed {
+                                    project_actions.push((
+                                        "cancel_add_task",
+                                        project.id,
+                                        String::new(),
+                                    ));
+                                }
+                                self.render_blank_warning(
+                                    ui,
+                                    BlankInput::NewTask(project.id),
+                                    "Task text can't be empty",
+                                );
+                            });
+                        }
+
+                        // Per-project "Add task" row; each project keeps its own draft
+                        if project.expanded && !self.presentation_mode {
+                            ui.add_space(8.0);
+                            self.render_add_task_row(ui, project, project_actions);
+                        }
+
+                        if !completed_tasks.is_empty() {
+                            ui.add_space(8.0);
+                            egui::CollapsingHeader::new(
+                                t(self.lang, Tr::CompletedCount)
+                                    .replace("{count}", &completed_tasks.len().to_string()),
+                            )
+                            .id_salt("completed_tasks")
+                            .default_open(false)
+                            // Nothing else is listed under "Done only"
+                            .open((self.status_filter == StatusFilter::Done).then_some(true))
+                            .show(ui, |ui| {
+                                for (task_idx, task) in completed_tasks.into_iter().enumerate() {
+                                    let key = egui::Id::new(("task", task.id));
+                                    self.lazy_row(ui, key, row_stamp(task), false, |app, ui| {
+                                        ui.add_space(8.0);
+                                        app.render_task_row(
+                                            ui,
+                                            project.id,
+                                            task,
+                                            active_count + task_idx + 1,
+                                            task_actions,
+                                        );
+                                    });
+                                }
+                            });
+                        }
+                    });
+                }
+            })
+            .response;
+
+        if self.scroll_to_project == Some(project.id) {
+            frame_response.scroll_to_me(None);
+            self.scroll_to_project = None;
+        }
+    }
+
+    fn render_add_task_row(
+        &mut self,
+        ui: &mut egui::Ui,
+        project: &Project,
+        project_actions: &mut Vec<ProjectAction>,
+    ) {
+        let mut draft = self
+            .new_task_texts
+            .get(&project.id)
+            .cloned()
+            .unwrap_or_default();
+        let at_limit = project.at_wip_limit();
+
+        ui.add_enabled_ui(!at_limit, |ui| {
+            ui.horizontal(|ui| {
+                let response = ui.add(egui::TextEdit::singleline(&mut draft).hint_text(
+                    if at_limit {
+                        "WIP limit reached"
+                    } else {
+                        t(self.lang, Tr::AddTaskHint)
+                    },
+                ));
+                let add_clicked = ui
+                    .button(egui::RichText::new(icons::icons::ICON_ADD).size(BUTTON_SIZE))
+                    .clicked();
+                let enter_pressed =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                if response.changed() {
+                    // Don't keep (and persist) empty drafts around
+                    if draft.is_empty() {
+                        self.new_task_texts.remove(&project.id);
+                    } else {
+                        self.new_task_texts.insert(project.id, draft.clone());
+                    }
+                }
+                if add_clicked || enter_pressed {
+                    if draft.trim().is_empty() {
+                        self.warn_blank(ui, BlankInput::DraftTask(project.id));
+                    } else {
+                        project_actions.push(("add_draft_task", project.id, String::new()));
+                    }
+                }
+                self.render_blank_warning(
+                    ui,
+                    BlankInput::DraftTask(project.id),
+                    "Task text can't be empty",
+                );
+            });
+        });
+    }
+
+    /// Draws a row of a scrolled list only while it is on screen. Off screen it just
+    /// takes up the height it had when last drawn, so long lists stay fast; rows
+    /// that were never drawn are always drawn once to measure them.
+    fn lazy_row(
+        &mut self,
+        ui: &mut egui::Ui,
+        key: egui::Id,
+        stamp: u64,
+        always: bool,
+        add_contents: impl FnOnce(&mut Self, &mut egui::Ui),
+    ) {
+        // Detached windows draw the same rows at their own sizes
+        let key = egui::Id::new((key, ui.ctx().viewport_id(), ui.layer_id()));
+        self.rows_seen.insert(key);
+        let top = ui.cursor().min.y;
+        if let Some(&(_, height)) = self
+            .row_heights
+            .get(&key)
+            .filter(|(measured, _)| *measured == stamp)
+        {
+            let rect = egui::Rect::from_min_size(
+                ui.cursor().min,
+                egui::vec2(ui.available_width(), height),
+            );
+            if !always && !ui.is_rect_visible(rect) {
+                ui.add_space(height);
+                return;
+            }
+        }
+        add_contents(self, ui);
+        self.row_heights
+            .insert(key, (stamp, ui.cursor().min.y - top));
+    }
+
+    /// Forgets row heights that can't be trusted any more: rows that weren't
+    /// reached last frame (deleted, filtered out, other view) and, when a filter
+    /// or mode changed, all of them.
+    fn begin_lazy_rows(&mut self) {
+        let seen = std::mem::take(&mut self.rows_seen);
+        self.row_heights.retain(|key, _| seen.contains(key));
+
+        let mut hasher = DefaultHasher::new();
+        (
+            &self.search_query,
+            self.fuzzy_search,
+            self.status_filter,
+            self.overdue_only,
+            self.flagged_only,
+            self.show_snoozed,
+            self.smart_order,
+            self.focus_view,
+            self.lang,
+            self.presentation_mode,
+            self.stale_after_days,
+        )
+            .hash(&mut hasher);
+        (
+            self.editing_project,
+            self.editing_task,
+            self.adding_task_to_project,
+        )
+            .hash(&mut hasher);
+        let view = hasher.finish();
+        if view != self.rows_view {
+            self.row_heights.clear();
+            self.rows_view = view;
+        }
+    }
+
+    fn warn_blank(&mut self, ui: &egui::Ui, input: BlankInput) {
+        let until = ui.input(|i| i.time) + BLANK_WARNING_SECS;
+        self.blank_warning = Some((input, until));
+    }
+
+    /// Short-lived warning next to `input` after it was submitted blank.
+    fn render_blank_warning(&mut self, ui: &mut egui::Ui, input: BlankInput, message: &str) {
+        let Some((warned, until)) = self.blank_warning else {
+            return;
+        };
+        if warned != input {
+            return;
+        }
+        let remaining = until - ui.input(|i| i.time);
+        if remaining <= 0.0 {
+            self.blank_warning = None;
+            return;
+        }
+
+        let opacity = (remaining / 0.5).min(1.0) as f32;
+        ui.colored_label(
+            egui::Color32::from_rgb(230, 150, 0).gamma_multiply(opacity),
+            format!("{} {}", icons::icons::ICON_WARNING, message),
+        );
+        ui.ctx().request_repaint();
+    }
+
+    fn render_task_row(
+        &mut self,
+        ui: &mut egui::Ui,
+        project_id: usize,
+        task: &Task,
+        number: usize, // 1-based position as displayed
+        task_actions: &mut Vec<TaskAction>,
+    ) {
+        // The row itself senses clicks so it can host a context menu;
+        // widgets inside it still take priority.
+        // Same footprint as `ui.horizontal`: full width, one widget row tall.
+        let row_rect = egui::Rect::from_min_size(
+            ui.cursor().min,
+            egui::vec2(ui.available_width(), ui.spacing().interact_size.y),
+        );
+        if self.selected_task == Some((project_id, task.id)) {
+            ui.painter().rect_filled(
+                row_rect.expand(2.0),
+                4.0,
+                ui.visuals().selection.bg_fill.gamma_multiply(0.3),
+            );
+        }
+        if let Some(progress) = self.completion_flash_progress(task.id, ui.input(|i| i.time)) {
+            ui.painter().rect_filled(
+                row_rect.expand(2.0),
+                4.0,
+                egui::Color32::from_rgb(80, 200, 120).gamma_multiply(0.5 * (1.0 - progress)),
+            );
+            ui.ctx().request_repaint();
+        }
+        let row = ui.scope_builder(
+            egui::UiBuilder::new()
+                .max_rect(row_rect)
+                .layout(egui::Layout::left_to_right(egui::Align::Center))
+                .sense(egui::Sense::click()),
+            |ui| {
+                // Checkbox for completion
+                // A task can't be checked off while its blocker is still open
+                let blocker = self
+                    .projects
+                    .iter()
+                    .find(|p| p.id == project_id)
+                    .and_then(|p| p.open_blocker(task))
+                    .map(|t| t.text.clone());
+                let mut completed = task.completed;
+                let mut checkbox = ui.add_enabled(
+                    !self.presentation_mode && (task.completed || blocker.is_none()),
+                    egui::Checkbox::without_text(&mut completed),
+                );
+                if let Some(blocker) = &blocker {
+                    checkbox = checkbox.on_disabled_hover_text(format!("Blocked by: {}", blocker));
+                }
+                if checkbox.changed() {
+                    task_actions.push(("toggle_complete", project_id, task.id, String::new()));
+                    // Only checking a task off gets feedback, not unchecking it
+                    if completed && self.completion_feedback {
+                        self.completion_flash = Some((task.id, ui.input(|i| i.time)));
+                    }
+                }
+
+                ui.label(
+                    egui::RichText::new(format!("{}.", number))
+                        .size(BUTTON_SIZE)
+                        .color(egui::Color32::GRAY),
+                );
+
+                // Task text and controls
+                if self.editing_task == Some((project_id, task.id)) {
+                    // Editing mode: show text input with confirmation buttons
+                    let response = ui.text_edit_singleline(&mut self.edit_task_text);
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        task_actions.push(("save_edit", project_id, task.id, String::new()));
+                    } else if response.lost_focus()
+                        && ui.input(|i| i.key_pressed(egui::Key::Escape))
+                    {
+                        task_actions.push(("stop_edit", project_id, task.id, String::new()));
+                    }
+
+                    self.render_due_editor(ui, task.id);
+                    self.render_blocker_picker(ui, project_id, task.id);
+
+                    // Optional time estimate
+                    let mut has_estimate = self.edit_task_estimate.is_some();
+                    if ui.checkbox(&mut has_estimate, "Estimate").changed() {
+                        self.edit_task_estimate = has_estimate.then_some(30);
+                    }
+                    if let Some(minutes) = &mut self.edit_task_estimate {
+                        ui.add(
+                            egui::DragValue::new(minutes)
+                                .range(5..=6000)
+                                .speed(5)
+                                .suffix(" min"),
+                        );
+                    }
+
+                    if ui.button(icons::icons::ICON_CHECK).clicked() {
+                        task_actions.push(("save_edit", project_id, task.id, String::new()));
+                    }
+                    if ui.button(icons::icons::ICON_CLOSE).clicked() {
+                        task_actions.push(("stop_edit", project_id, task.id, String::new()));
+                    }
+                    if self.task_edit_dirty() {
+                        unsaved_edit_marker(ui);
+                    }
+                    None
+                } else {
+                    // Display mode: show label with edit button
+                    if let Some(blocker) = &blocker {
+                        ui.label(
+                            egui::RichText::new(icons::icons::ICON_LOCK)
+                                .size(BUTTON_SIZE)
+                                .color(egui::Color32::GRAY),
+                        )
+                        .on_hover_text(format!("Blocked by: {}", blocker));
+                    }
+                    let text_color = if task.completed || blocker.is_some() {
+                        ui.visuals().weak_text_color()
+                    } else {
+                        ui.visuals().text_color()
+                    };
+                    // Double-click the text to start editing
+                    let label_response = self.render_task_text(ui, task, text_color);
+                    if label_response.double_clicked() && !self.presentation_mode {
+                        task_actions.push(("start_edit", project_id, task.id, task.text.clone()));
+                    }
+
+                    if let Some(due_date) = task.due_date {
+                        let mut due = due_date.format("%d/%m/%Y").to_string();
+                        if let Some(due_time) = task.due_time {
+                            due.push_str(&due_time.format(" %H:%M").to_string());
+                        }
+                        let icon = if task.is_snoozed(Local::now().date_naive()) {
+                            icons::icons::ICON_SNOOZE
+                        } else {
+                            icons::icons::ICON_EVENT
+                        };
+                        ui.label(
+                            egui::RichText::new(format!("{} {}", icon, due))
+                                .size(BUTTON_SIZE)
+                                .color(egui::Color32::GRAY),
+                        );
+
+                        // "in 3 days" / "due today" / "2 days ago" for open tasks
+                        if !task.completed {
+                            let now = Local::now().naive_local();
+                            let (countdown, mut color) = format_due(due_date, now.date());
+                            // A due time earlier today already counts as overdue
+                            if task.is_overdue(now) {
+                                color = egui::Color32::RED;
+                            }
+                            ui.label(
+                                egui::RichText::new(countdown)
+                                    .size(BUTTON_SIZE)
+                                    .color(color),
+                            );
+                        }
+                    }
+
+                    if let Some(minutes) = task.estimate_minutes {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{} {}",
+                                icons::icons::ICON_TIMER,
+                                format_minutes(minutes)
+                            ))
+                            .size(BUTTON_SIZE)
+                            .color(egui::Color32::GRAY),
+                        )
+                        .on_hover_text("Estimated time");
+                    }
+
+                    // Open for longer than the stale threshold (older saves have no age)
+                    let age = task.open_age_days(Local::now());
+                    if let (Some(age), Some(limit)) = (age, self.stale_after_days) {
+                        if age >= limit as i64 {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} {}d",
+                                    icons::icons::ICON_HOURGLASS_BOTTOM,
+                                    age
+                                ))
+                                .size(BUTTON_SIZE)
+                                .color(egui::Color32::from_rgb(200, 140, 60)),
+                            )
+                            .on_hover_text(format!("Stale: open for {} days", age));
+                        }
+                    }
+
+                    // Muted "completed 2h ago" hint
+                    if let Some(completed_at) = task.completed_at.filter(|_| task.completed) {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "completed {}",
+                                format_time_since(completed_at, Local::now())
+                            ))
+                            .size(BUTTON_SIZE)
+                            .color(egui::Color32::GRAY),
+                        );
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if !self.presentation_mode {
+                            // Delete task button
+                            if ui.button(icons::icons::ICON_DELETE).clicked() {
+                                task_actions.push(("delete", project_id, task.id, String::new()));
+                            }
+
+                            // Edit task button
+                            if ui.button(icons::icons::ICON_EDIT).clicked() {
+                                task_actions.push((
+                                    "start_edit",
+                                    project_id,
+                                    task.id,
+                                    task.text.clone(),
+                                ));
+                            }
+                        }
+
+                        // Flag (star) toggle
+                        let (flag_icon, flag_color) = if task.flagged {
+                            (
+                                icons::icons::ICON_STAR,
+                                egui::Color32::from_rgb(240, 180, 0),
+                            )
+                        } else {
+                            (icons::icons::ICON_STAR_BORDER, egui::Color32::GRAY)
+                        };
+                        if self.presentation_mode {
+                            // Keep the flag visible, just not clickable
+                            if task.flagged {
+                                ui.label(egui::RichText::new(flag_icon).color(flag_color));
+                            }
+                        } else if ui
+                            .button(egui::RichText::new(flag_icon).color(flag_color))
+                            .on_hover_text(if task.flagged { "Unflag" } else { "Flag" })
+                            .clicked()
+                        {
+                            task_actions.push(("toggle_flag", project_id, task.id, String::new()));
+                        }
+
+                        // Notes section toggle, highlighted when there are notes
+                        let notes_color = if task.notes.is_empty() {
+                            egui::Color32::GRAY
+                        } else {
+                            ui.visuals().selection.bg_fill
+                        };
+                        if ui
+                            .button(
+                                egui::RichText::new(icons::icons::ICON_NOTES).color(notes_color),
+                            )
+                            .on_hover_text(if task.expanded {
+                                "Hide notes"
+                            } else {
+                                "Show notes"
+                            })
+                            .clicked()
+                        {
+                            task_actions.push((
+                                "toggle_details",
+                                project_id,
+                                task.id,
+                                String::new(),
+                            ));
+                        }
+                    });
+                    Some(label_response)
+                }
+            },
+        );
+
+        // Click the row to select it; right-click anywhere on it (text included)
+        // for task operations
+        if let Some(label_response) = row.inner {
+            let response = row.response | label_response;
+            if response.clicked() {
+                task_actions.push(("select", project_id, task.id, String::new()));
+            }
+            if !self.presentation_mode {
+                response.context_menu(|ui| {
+                    self.render_task_context_menu(ui, project_id, task, task_actions);
+                });
+            }
+        }
+
+        if task.expanded {
+            ui.indent(("task_notes", task.id), |ui| {
+                let mut notes = task.notes.clone();
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut notes)
+                        .hint_text("Notes")
+                        .interactive(!self.presentation_mode)
+                        .desired_rows(2)
+                        .desired_width(f32::INFINITY),
+                );
+                if response.changed() {
+                    task_actions.push(("set_notes", project_id, task.id, notes));
+                }
+            });
+        }
+    }
+
+    /// How far (0..1) the completion flash for `task_id` has run, if it still is.
+    fn completion_flash_progress(&self, task_id: usize, now: f64) -> Option<f32> {
+        let (flash_task, start) = self.completion_flash?;
+        let progress = (now - start) / COMPLETION_FLASH_SECS;
+        (flash_task == task_id && progress < 1.0).then_some(progress as f32)
+    }
+
+    /// Indices into `self.projects` in display order: pinned projects first,
+    /// each group keeping its stored order.
+    fn project_display_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.projects.len()).collect();
+        order.sort_by_key(|&idx| !self.projects[idx].pinned);
+        order
+    }
+
+    /// Any search or filter narrowing down the task list.
+    fn is_filtering(&self) -> bool {
+        !self.search_query.trim().is_empty()
+            || self.status_filter != StatusFilter::All
+            || self.overdue_only
+            || self.flagged_only
+    }
+
+    /// Removable chips for each active filter, plus a reset for all of them.
+    fn render_filter_chips(&mut self, ui: &mut egui::Ui) {
+        if !self.is_filtering() {
+            return;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            let chip = |ui: &mut egui::Ui, label: String| {
+                ui.add(
+                    egui::Button::new(
+                        egui::RichText::new(format!("{} {}", label, icons::icons::ICON_CLOSE))
+                            .size(BUTTON_SIZE),
+                    )
+                    .corner_radius(12.0),
+                )
+                .on_hover_text("Remove this filter")
+                .clicked()
+            };
+
+            let query = self.search_query.trim();
+            if !query.is_empty() {
+                let kind = if self.fuzzy_search { "fuzzy" } else { "search" };
+                if chip(ui, format!("{}: \"{}\"", kind, query)) {
+                    self.search_query.clear();
+                }
+            }
+            if self.status_filter != StatusFilter::All
+                && chip(ui, self.status_filter.label(self.lang).to_lowercase())
+            {
+                self.status_filter = StatusFilter::All;
+                self.dirty = true;
+            }
+            if self.overdue_only && chip(ui, "overdue only".into()) {
+                self.overdue_only = false;
+            }
+            if self.flagged_only && chip(ui, "flagged only".into()) {
+                self.flagged_only = false;
+            }
+
+            if ui
+                .small_button(egui::RichText::new("Clear all filters").size(BUTTON_SIZE))
+                .clicked()
+            {
+                self.search_query.clear();
+                if self.status_filter != StatusFilter::All {
+                    self.status_filter = StatusFilter::All;
+                    self.dirty = true;
+                }
+                self.overdue_only = false;
+                self.flagged_only = false;
+            }
+        });
+    }
+
+    /// The project's tasks that pass the search, its own search and the filters,
+    /// in display order.
+    /// Fuzzy results are ranked best match first; otherwise the stored order is kept.
+    fn visible_tasks<'a>(&self, project: &'a Project) -> Vec<&'a Task> {
+        let now = Local::now().naive_local();
+        let project_query = self
+            .project_search
+            .get(&project.id)
+            .map(|q| q.trim().to_lowercase())
+            .unwrap_or_default();
+        let filtered = project
+            .tasks
+            .iter()
+            .filter(|t| self.status_filter.matches(t))
+            .filter(|t| !self.overdue_only || t.is_overdue(now))
+            .filter(|t| !self.flagged_only || t.flagged)
+            .filter(|t| self.show_snoozed || !t.is_snoozed(now.date()))
+            .filter(|t| project_query.is_empty() || t.text.to_lowercase().contains(&project_query));
+
+        let query = self.search_query.trim();
+        // Fuzzy results are ranked by score instead
+        if query.is_empty() || !self.fuzzy_search {
+            let query = query.to_lowercase();
+            let mut tasks: Vec<&Task> = filtered
+                .filter(|t| query.is_empty() || t.text.to_lowercase().contains(&query))
+                .collect();
+            if self.smart_order {
+                // Stable, so ties keep the stored order
+                tasks.sort_by_key(|t| (due_date_key(t), !t.flagged));
+            } else if project.sort == TaskSort::DueDate {
+                tasks.sort_by_key(|t| due_date_key(t));
+            }
+            tasks
+        } else {
+            let mut scored: Vec<(i32, &Task)> = filtered
+                .filter_map(|t| fuzzy_match(query, &t.text).map(|(score, _)| (score, t)))
+                .collect();
+            // Stable, so equal scores keep their stored order
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            scored.into_iter().map(|(_, t)| t).collect()
+        }
+    }
+
+    /// Due date/time controls shown while a task is being edited.
+    fn render_due_editor(&mut self, ui: &mut egui::Ui, task_id: usize) {
+        let mut has_date = self.edit_task_due_date.is_some();
+        if ui.checkbox(&mut has_date, "Due").changed() {
+            self.edit_task_due_date = has_date.then(|| Local::now().date_naive());
+        }
+        let Some(date) = &mut self.edit_task_due_date else {
+            return;
+        };
+        ui.add(egui_extras::DatePickerButton::new(date).id_salt(&format!("due_{}", task_id)));
+
+        let mut has_time = self.edit_task_due_time.is_some();
+        if ui.checkbox(&mut has_time, "at").changed() {
+            self.edit_task_due_time = has_time.then(|| NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        }
+        if let Some(time) = self.edit_task_due_time {
+            let mut hour = time.hour();
+            let mut minute = time.minute();
+            ui.add(egui::DragValue::new(&mut hour).range(0..=23));
+            ui.label(":");
+            ui.add(egui::DragValue::new(&mut minute).range(0..=59));
+            self.edit_task_due_time = NaiveTime::from_hms_opt(hour, minute, 0);
+        }
+    }
+
+    /// "Blocked by" picker shown while a task is being edited; lists the
+    /// project's other tasks, minus those already waiting on this one.
+    fn render_blocker_picker(&mut self, ui: &mut egui::Ui, project_id: usize, task_id: usize) {
+        let Some(project) = self.projects.iter().find(|p| p.id == project_id) else {
+            return;
+        };
+        let candidates: Vec<&Task> = project
+            .tasks
+            .iter()
+            // Anything already waiting on this task would close a loop
+            .filter(|t| !project.depends_on(t.id, task_id))
+            .collect();
+        let selected_text = self
+            .edit_task_blocked_by
+            .and_then(|id| project.tasks.iter().find(|t| t.id == id))
+            .map_or("Not blocked".to_string(), |t| {
+                format!("Blocked by: {}", t.text)
+            });
+
+        egui::ComboBox::from_id_salt(("blocked_by", task_id))
+            .selected_text(selected_text)
+            .width(160.0)
+            .truncate()
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.edit_task_blocked_by, None, "Not blocked");
+                for candidate in candidates {
+                    ui.selectable_value(
+                        &mut self.edit_task_blocked_by,
+                        Some(candidate.id),
+                        &candidate.text,
+                    );
+                }
+            });
+    }
+
+    /// Task text in display mode, with http(s) links rendered as clickable hyperlinks
+    /// and search matches emphasised. Returns the (click-sensing) response of the
+    /// plain-text parts.
+    fn render_task_text(
+        &self,
+        ui: &mut egui::Ui,
+        task: &Task,
+        text_color: egui::Color32,
+    ) -> egui::Response {
+        let highlights = self.search_highlights(&task.text);
+
+        ui.scope(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            let mut text_response: Option<egui::Response> = None;
+            let mut link_response: Option<egui::Response> = None;
+            // Segments cover the text back to back, so offsets can simply accumulate
+            let mut offset = 0;
+
+            for (segment, is_url) in split_urls(&task.text) {
+                let segment_start = offset;
+                offset += segment.len();
+
+                if is_url {
+                    let mut label = egui::RichText::new(segment).size(TEXT_SIZE);
+                    if task.completed {
+                        label = label.strikethrough();
+                    }
+                    let response = ui.add(egui::Hyperlink::from_label_and_url(label, segment));
+                    link_response.get_or_insert(response);
+                    continue;
+                }
+
+                for (range, matched) in split_highlights(segment_start..offset, &highlights) {
+                    let mut text = egui::RichText::new(&task.text[range]).size(TEXT_SIZE);
+                    if task.completed {
+                        text = text.strikethrough();
+                    }
+                    text = if matched {
+                        text.strong()
+                            .color(ui.visuals().selection.stroke.color)
+                            .background_color(ui.visuals().selection.bg_fill)
+                    } else {
+                        text.color(text_color)
+                    };
+                    let response = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                    text_response = Some(match text_response {
+                        Some(previous) => previous | response,
+                        None => response,
+                    });
+                }
+            }
+
+            // A task that is only a link still needs a response to hang the menu on
+            text_response
+                .or(link_response)
+                .unwrap_or_else(|| ui.label(""))
+        })
+        .inner
+    }
+
+    /// Byte ranges of `text` matched by the current search (empty when not searching).
+    fn search_highlights(&self, text: &str) -> Vec<std::ops::Range<usize>> {
+        let query = self.search_query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        if self.fuzzy_search {
+            let Some((_, matched)) = fuzzy_match(query, text) else {
+                return Vec::new();
+            };
+            text.char_indices()
+                .enumerate()
+                .filter(|(idx, _)| matched.contains(idx))
+                .map(|(_, (byte, c))| byte..byte + c.len_utf8())
+                .collect()
+        } else {
+            find_case_insensitive(text, query)
+        }
+    }
+
+    fn render_task_context_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        project_id: usize,
+        task: &Task,
+        task_actions: &mut Vec<TaskAction>,
+    ) {
+        if ui
+            .button(format!("{} Edit", icons::icons::ICON_EDIT))
+            .clicked()
+        {
+            task_actions.push(("start_edit", project_id, task.id, task.text.clone()));
+            ui.close();
+        }
+
+        let toggle_label = if task.completed {
+            format!(
+                "{} Mark incomplete",
+                icons::icons::ICON_CHECK_BOX_OUTLINE_BLANK
+            )
+        } else {
+            format!("{} Mark complete", icons::icons::ICON_CHECK_BOX)
+        };
+        let blocked = !task.completed
+            && self
+                .projects
+                .iter()
+                .find(|p| p.id == project_id)
+                .is_some_and(|p| p.open_blocker(task).is_some());
+        if ui
+            .add_enabled(!blocked, egui::Button::new(toggle_label))
+            .on_disabled_hover_text("Blocked by an unfinished task")
+            .clicked()
+        {
+            task_actions.push(("toggle_complete", project_id, task.id, String::new()));
+            ui.close();
+        }
+
+        if ui
+            .button(format!("{} Duplicate", icons::icons::ICON_CONTENT_COPY))
+            .clicked()
+        {
+            task_actions.push(("duplicate", project_id, task.id, String::new()));
+            ui.close();
+        }
+
+        if !task.completed {
+            ui.menu_button(format!("{} Snooze", icons::icons::ICON_SNOOZE), |ui| {
+                let today = Local::now().date_naive();
+                let mut target = None;
+                if ui.button("Until tomorrow").clicked() {
+                    target = today.succ_opt();
+                }
+                if ui.button("For a week").clicked() {
+                    target = Some(today + chrono::Duration::days(7));
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui_extras::DatePickerButton::new(&mut self.snooze_pick_date)
+                            .id_salt(&format!("snooze_{}", task.id)),
+                    );
+                    if ui.button("Snooze").clicked() {
+                        target = Some(self.snooze_pick_date);
+                    }
+                });
+                if let Some(date) = target {
+                    task_actions.push(("snooze", project_id, task.id, date.to_string()));
+                    ui.close();
+                }
+            });
+        }
+
+        ui.menu_button(
+            format!("{} Move to project", icons::icons::ICON_DRIVE_FILE_MOVE),
+            |ui| {
+                let mut has_targets = false;
+                for target in self.projects.iter().filter(|p| p.id != project_id) {
+                    has_targets = true;
+                    // Completed tasks don't count towards a WIP limit
+                    let blocked = !task.completed && target.at_wip_limit();
+                    let response = ui
+                        .add_enabled(!blocked, egui::Button::new(&target.name))
+                        .on_disabled_hover_text("WIP limit reached");
+                    if response.clicked() {
+                        task_actions.push(("move", project_id, task.id, target.id.to_string()));
+                        ui.close();
+                    }
+                }
+                if !has_targets {
+                    ui.label("No other projects");
+                }
+            },
+        );
+
+        ui.separator();
+
+        if ui
+            .button(format!("{} Delete", icons::icons::ICON_DELETE))
+            .clicked()
+        {
+            task_actions.push(("delete", project_id, task.id, String::new()));
+            ui.close();
+        }
+    }
+
+    fn apply_project_action(&mut self, action: &str, project_id: usize, text: String) {
+        match action {
+            "start_edit" => {
+                self.start_edit_project(project_id, text);
+            }
+            "save_edit" => {
+                self.save_edit_project(project_id);
+            }
+            "stop_edit" => {
+                self.editing_project = None;
+            }
+            "toggle_expanded" => {
+                if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+                    if project.expanded {
+                        project.expanded = false;
+                        self.dirty = true;
+                    } else {
+                        self.expand_project(project_id);
+                    }
+                    self.focused_project = Some(project_id);
+                }
+            }
+            "add_draft_task" => {
+                if let Some(draft) = self.new_task_texts.remove(&project_id) {
+                    self.add_task_to_project(project_id, draft);
+                }
+            }
+            "export" => {
+                self.export_project(project_id);
+            }
+            "detach" => {
+                self.detached_projects.insert(project_id);
+            }
+            "paste_tasks" => {
+                self.paste_target = Some(project_id);
+                self.paste_text.clear();
+            }
+            "toggle_pinned" => {
+                if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+                    project.pinned = !project.pinned;
+                    self.dirty = true;
+                }
+            }
+            "set_sort" => {
+                if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+                    project.sort = match text.as_str() {
+                        "due_date" => TaskSort::DueDate,
+                        _ => TaskSort::Manual,
+                    };
+                    self.dirty = true;
+                }
+            }
+            "persist_sort" => {
+                if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+                    project.persist_sort();
+                    self.dirty = true;
+                }
+            }
+            "complete_all" => {
+                self.push_undo();
+                self.set_project_completed(project_id, true);
+            }
+            "uncomplete_all" => {
+                self.push_undo();
+                self.set_project_completed(project_id, false);
+            }
+            "delete" => {
+                self.push_undo();
+                self.remove_project(project_id);
+            }
+            "add_task" => {
+                // The inline entry is only drawn inside an expanded project
+                self.expand_project(project_id);
+                self.adding_task_to_project = Some(project_id);
+                self.focus_add_task_field = true;
+                // Initialize the text field for this project if it doesn't exist
+                self.right_click_task_text.entry(project_id).or_default();
+            }
+            "submit_task" => {
+                self.submit_new_task(project_id);
+            }
+            "cancel_add_task" => {
+                self.cancel_new_task(project_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Opens the rename editor for a project, unless another one has unsaved
+    /// changes (then the keep/discard prompt asks first).
+    fn start_edit_project(&mut self, project_id: usize, name: String) {
+        if self.project_edit_dirty() && self.editing_project != Some(project_id) {
+            self.pending_edit = Some(PendingEdit::Project(project_id, name));
+            return;
+        }
+        self.editing_project = Some(project_id);
+        self.edit_project_original = name.clone();
+        self.edit_project_text = name;
+        self.edit_project_error = None;
+        let project = self.projects.iter().find(|p| p.id == project_id);
+        self.edit_project_wip_limit = project.and_then(|p| p.wip_limit);
+        self.edit_project_description = project.map(|p| p.description.clone()).unwrap_or_default();
+    }
+
+    /// Applies the rename editor to the project. A blank name keeps the old one.
+    fn save_edit_project(&mut self, project_id: usize) {
+        // Keep the editor open so the user can pick another name
+        if self.project_name_taken(&self.edit_project_text, Some(project_id)) {
+            self.edit_project_error = Some("A project with this name already exists".into());
+            return;
+        }
+        if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+            if !self.edit_project_text.trim().is_empty() {
+                project.name = self.edit_project_text.clone();
+            }
+            project.wip_limit = self.edit_project_wip_limit;
+            project.description = self.edit_project_description.trim().to_string();
+            self.dirty = true;
+        }
+        self.editing_project = None;
+    }
+
+    /// Adds the inline "New Task" entry's text as a task, then closes the entry
+    /// (or clears it for the next task in rapid entry). A blank entry stays open.
+    fn submit_new_task(&mut self, project_id: usize) {
+        let task_text = self
+            .right_click_task_text
+            .get(&project_id)
+            .cloned()
+            .unwrap_or_default();
+        if self.add_task_to_project(project_id, task_text).is_none() {
+            return;
+        }
+        let at_limit = self
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .is_some_and(|p| p.at_wip_limit());
+        if self.rapid_task_entry && !at_limit {
+            self.right_click_task_text.insert(project_id, String::new());
+            self.focus_add_task_field = true;
+        } else {
+            self.cancel_new_task(project_id);
+        }
+    }
+
+    fn cancel_new_task(&mut self, project_id: usize) {
+        self.adding_task_to_project = None;
+        // Drop the draft so it isn't saved
+        self.right_click_task_text.remove(&project_id);
+    }
+
+    fn apply_task_action(&mut self, action: &str, project_id: usize, task_id: usize, text: String) {
+        match action {
+            "start_edit" => {
+                if self.task_edit_dirty() && self.editing_task != Some((project_id, task_id)) {
+                    self.pending_edit = Some(PendingEdit::Task(project_id, task_id, text));
+                    return;
+                }
+                self.editing_task = Some((project_id, task_id));
+                self.edit_task_original = text.clone();
+                self.edit_task_text = text;
+                let due = self
+                    .find_task_mut(project_id, task_id)
+                    .map(|t| (t.due_date, t.due_time));
+                (self.edit_task_due_date, self.edit_task_due_time) = due.unwrap_or_default();
+                self.edit_task_blocked_by = self
+                    .find_task_mut(project_id, task_id)
+                    .and_then(|t| t.blocked_by);
+                self.edit_task_estimate = self
+                    .find_task_mut(project_id, task_id)
+                    .and_then(|t| t.estimate_minutes);
+            }
+            "save_edit" => {
+                let new_text = self.edit_task_text.clone();
+                let due_date = self.edit_task_due_date;
+                let due_time = self.edit_task_due_time.filter(|_| due_date.is_some());
+                let project = self.projects.iter().find(|p| p.id == project_id);
+                let blocked_by = self
+                    .edit_task_blocked_by
+                    .filter(|&id| project.is_some_and(|p| !p.depends_on(id, task_id)));
+                let estimate = self.edit_task_estimate;
+                let mut due_changed = false;
+                if let Some(task) = self.find_task_mut(project_id, task_id) {
+                    task.blocked_by = blocked_by;
+                    task.estimate_minutes = estimate;
+                    if !new_text.trim().is_empty() {
+                        task.text = new_text;
+                    }
+                    // A hand-picked due date replaces any snooze
+                    if task.due_date != due_date {
+                        task.snoozed = false;
+                    }
+                    due_changed = (task.due_date, task.due_time) != (due_date, due_time);
+                    task.due_date = due_date;
+                    task.due_time = due_time;
+                    self.dirty = true;
+                }
+                // A changed due time gets a fresh reminder
+                if due_changed {
+                    self.fired_reminders.remove(&task_id);
+                }
+                self.editing_task = None;
+            }
+            "stop_edit" => {
+                self.editing_task = None;
+            }
+            "toggle_complete" => {
+                let blocked = self
+                    .projects
+                    .iter()
+                    .find(|p| p.id == project_id)
+                    .is_some_and(|p| {
+                        p.tasks
+                            .iter()
+                            .find(|t| t.id == task_id)
+                            .is_some_and(|t| !t.completed && p.open_blocker(t).is_some())
+                    });
+                if blocked {
+                    return;
+                }
+                if let Some(task) = self.find_task_mut(project_id, task_id) {
+                    let completed = !task.completed;
+                    task.set_completed(completed);
+                    self.dirty = true;
+                }
+            }
+            "snooze" => {
+                let Ok(date) = text.parse::<NaiveDate>() else {
+                    return;
+                };
+                if let Some(task) = self.find_task_mut(project_id, task_id) {
+                    task.due_date = Some(date);
+                    task.snoozed = true;
+                    self.dirty = true;
+                }
+                // Remind again once the new due time passes
+                self.fired_reminders.remove(&task_id);
+            }
+            "toggle_details" => {
+                if let Some(task) = self.find_task_mut(project_id, task_id) {
+                    task.expanded = !task.expanded;
+                    self.dirty = true;
+                }
+            }
+            "set_notes" => {
+                if let Some(task) = self.find_task_mut(project_id, task_id) {
+                    task.notes = text;
+                    self.dirty = true;
+                }
+            }
+            "toggle_flag" => {
+                if let Some(task) = self.find_task_mut(project_id, task_id) {
+                    task.flagged = !task.flagged;
+                    self.dirty = true;
+                }
+            }
+            "duplicate" => {
+                self.duplicate_task(project_id, task_id);
+            }
+            "move" => {
+                if let Ok(target_id) = text.parse() {
+                    self.move_task(project_id, task_id, target_id);
+                }
+            }
+            "delete" => {
+                self.push_undo();
+                self.remove_task(project_id, task_id);
+                if self.selected_task == Some((project_id, task_id)) {
+                    self.selected_task = None;
+                }
+            }
+            "select" => {
+                self.selected_task = Some((project_id, task_id));
+            }
+            _ => {}
+        }
+    }
+
+    fn render_notes_canvas(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let rect = ui.max_rect();
+
+            // Detect middle mouse or two-finger drag for panning
+            let canvas_response = ui.interact(
+                rect,
+                egui::Id::new("canvas_interact"),
+                egui::Sense::click_and_drag(),
+            );
+
+            // Pan with middle mouse button
+            if canvas_response.dragged_by(egui::PointerButton::Middle) {
+                self.notes_canvas.scene_rect = self
+                    .notes_canvas
+                    .scene_rect
+                    .translate(canvas_response.drag_delta());
+            }
+
+            // Pan with two-finger trackpad drag
+            // translation_delta() provides panning from scrolling or pan gestures
+            let pan_delta = ui.input(|i| i.translation_delta());
+            if pan_delta != egui::Vec2::ZERO {
+                self.notes_canvas.scene_rect = self.notes_canvas.scene_rect.translate(pan_delta);
+            }
 
             // Render background with pan offset
             self.render_canvas_background(ui);
@@ -941,6 +4621,9 @@ impl TodoApp {
                             .desired_width(ui.available_width()),
                     );
 
+                    if response.changed() {
+                        self.dirty = true;
+                    }
                     if response.clicked_elsewhere() {
                         actions.push(("stop_editing", textbox_id));
                     }
@@ -1002,6 +4685,7 @@ impl TodoApp {
             if header_drag_delta != egui::Vec2::ZERO {
                 text_box.is_dragging = true;
                 text_box.position += header_drag_delta;
+                self.dirty = true;
             } else {
                 text_box.is_dragging = false;
             }
@@ -1048,6 +4732,7 @@ impl TodoApp {
 
                 text_box.size += delta;
                 text_box.size = text_box.size.max(text_box.min_size);
+                self.dirty = true;
             }
 
             // Visual feedback for resize handle
@@ -1077,6 +4762,7 @@ impl TodoApp {
                     self.notes_canvas
                         .text_boxes
                         .retain(|tb| tb.id != textbox_id);
+                    self.dirty = true;
                 }
                 "edit_title" => {
                     if let Some(textbox) = self
@@ -1098,6 +4784,7 @@ impl TodoApp {
                     {
                         if !self.temp_title_text.trim().is_empty() {
                             textbox.title = self.temp_title_text.clone();
+                            self.dirty = true;
                         }
                     }
                     self.editing_title = None;
@@ -1121,94 +4808,947 @@ impl TodoApp {
     fn handle_context_menu(&mut self, ui: &mut egui::Ui) {
         let pan_offset = self.notes_canvas.scene_rect.min.to_vec2();
 
-        // Detect right-click
-        ui.input(|i| {
-            if i.pointer.secondary_clicked() {
-                if let Some(screen_pos) = i.pointer.interact_pos() {
-                    // Convert screen position to canvas position
-                    let canvas_pos = screen_pos - pan_offset;
+        // Detect right-click
+        ui.input(|i| {
+            if i.pointer.secondary_clicked() {
+                if let Some(screen_pos) = i.pointer.interact_pos() {
+                    // Convert screen position to canvas position
+                    let canvas_pos = screen_pos - pan_offset;
+
+                    // Check if click is not on any text box
+                    let on_textbox = self.notes_canvas.text_boxes.iter().any(|tb| {
+                        egui::Rect::from_min_size(tb.position, tb.size).contains(canvas_pos)
+                    });
+
+                    if !on_textbox {
+                        self.context_menu_pos = Some(screen_pos);
+                    }
+                }
+            }
+        });
+
+        // Show context menu
+        if let Some(menu_screen_pos) = self.context_menu_pos {
+            egui::Area::new(egui::Id::new("context_menu"))
+                .fixed_pos(menu_screen_pos)
+                .show(ui.ctx(), |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        if ui.button("Create Text Box").clicked() {
+                            let canvas_pos = menu_screen_pos - pan_offset;
+                            self.create_text_box_at(canvas_pos);
+                            self.context_menu_pos = None;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.context_menu_pos = None;
+                        }
+                    });
+                });
+        }
+
+        // Close menu on click elsewhere
+        ui.input(|i| {
+            if i.pointer.primary_clicked() && self.context_menu_pos.is_some() {
+                self.context_menu_pos = None;
+            }
+        });
+    }
+
+    fn create_text_box_at(&mut self, pos: egui::Pos2) {
+        let text_box = TextBox {
+            id: self.notes_canvas.next_textbox_id,
+            title: format!("Note {}", self.notes_canvas.next_textbox_id),
+            position: pos,
+            size: egui::vec2(400.0, 250.0), // Fixed default size
+            content: String::new(),
+            auto_height: false, // Start in manual mode as requested
+            min_size: egui::vec2(150.0, 80.0),
+            is_dragging: false,
+        };
+
+        self.notes_canvas.text_boxes.push(text_box);
+        self.notes_canvas.next_textbox_id += 1;
+        self.dirty = true;
+    }
+
+    // Export methods
+    fn export_csv(&mut self) {
+        // A cancelled dialog returns None and nothing is written
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("todo_tasks.csv")
+            .save_file()
+        {
+            if let Err(err) = std::fs::write(&path, self.tasks_to_csv()) {
+                self.export_error = Some(format!("Could not write {}: {}", path.display(), err));
+            }
+        }
+    }
+
+    fn export_json(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("todo_export.json")
+            .save_file()
+        {
+            let export = TodoExport {
+                projects: self.projects.clone(),
+            };
+            if let Ok(json) = serde_json::to_string_pretty(&export) {
+                if let Err(err) = std::fs::write(&path, json) {
+                    self.export_error =
+                        Some(format!("Could not write {}: {}", path.display(), err));
+                }
+            }
+        }
+    }
+
+    fn import_json(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        {
+            match std::fs::read(&path) {
+                Ok(bytes) => self.load_import(&bytes),
+                Err(err) => self.import_error = Some(format!("Could not read file: {}", err)),
+            }
+        }
+    }
+
+    /// Writes one project, with its tasks, to a JSON file.
+    fn export_project(&mut self, project_id: usize) {
+        let Some(project) = self.projects.iter().find(|p| p.id == project_id) else {
+            return;
+        };
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name(format!("{}.json", project.name))
+            .save_file()
+        {
+            if let Ok(json) = serde_json::to_string_pretty(project) {
+                if let Err(err) = std::fs::write(&path, json) {
+                    self.export_error =
+                        Some(format!("Could not write {}: {}", path.display(), err));
+                }
+            }
+        }
+    }
+
+    /// Parses an export file. A full export is held until the user picks Replace
+    /// or Merge; a single-project export is added as a new project right away.
+    fn load_import(&mut self, bytes: &[u8]) {
+        let err = match serde_json::from_slice::<TodoExport>(bytes) {
+            Ok(export) => {
+                self.pending_import = Some(export.projects);
+                self.import_error = None;
+                return;
+            }
+            Err(err) => err,
+        };
+        match serde_json::from_slice::<Project>(bytes) {
+            Ok(project) => {
+                self.import_error = None;
+                self.apply_import(vec![project], false);
+            }
+            Err(_) => self.import_error = Some(format!("Not a valid todo export: {}", err)),
+        }
+    }
+
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let hovering = ctx.input(|i| !i.raw.hovered_files.is_empty());
+        if hovering {
+            // Drop-target highlight over the whole window
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                egui::Id::new("file_drop_overlay"),
+            ));
+            let screen_rect = ctx.content_rect();
+            let accent = ctx.style().visuals.selection.bg_fill;
+            painter.rect_filled(screen_rect, 0.0, accent.gamma_multiply(0.25));
+            painter.rect_stroke(
+                screen_rect.shrink(4.0),
+                8.0,
+                egui::Stroke::new(3.0, accent),
+                egui::StrokeKind::Inside,
+            );
+            painter.text(
+                screen_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                format!(
+                    "{} Drop a .json export to import",
+                    icons::icons::ICON_UPLOAD
+                ),
+                egui::FontId::proportional(HEADING_SIZE),
+                ctx.style().visuals.strong_text_color(),
+            );
+        }
+
+        // Only the first file counts when several are dropped at once
+        let Some(file) = ctx.input(|i| i.raw.dropped_files.first().cloned()) else {
+            return;
+        };
+        let is_json = file
+            .path
+            .as_ref()
+            .and_then(|p| p.extension())
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or_else(|| file.name.to_lowercase().ends_with(".json"));
+        if !is_json {
+            self.import_error = Some("Only .json export files can be imported".into());
+            return;
+        }
+
+        if let Some(bytes) = &file.bytes {
+            self.load_import(bytes);
+        } else if let Some(path) = &file.path {
+            match std::fs::read(path) {
+                Ok(bytes) => self.load_import(&bytes),
+                Err(err) => self.import_error = Some(format!("Could not read file: {}", err)),
+            }
+        }
+    }
+
+    fn render_export_error_window(&mut self, ctx: &egui::Context) {
+        let Some(error) = &self.export_error else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+
+        egui::Window::new("Export failed")
+            .id(egui::Id::new("export_error_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+                if ui.button("OK").clicked() {
+                    close = true;
+                }
+            });
+
+        if close || !open {
+            self.export_error = None;
+        }
+    }
+
+    fn render_import_window(&mut self, ctx: &egui::Context) {
+        let mut open = true;
+        let mut choice: Option<bool> = None; // Some(true) = replace, Some(false) = merge
+        let mut close = false;
+
+        egui::Window::new("Import")
+            .id(egui::Id::new("import_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if let Some(error) = &self.import_error {
+                    ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+                    if ui.button("OK").clicked() {
+                        close = true;
+                    }
+                    return;
+                }
+
+                let Some(projects) = &self.pending_import else {
+                    return;
+                };
+                let task_count: usize = projects.iter().map(|p| p.tasks.len()).sum();
+                ui.label(format!(
+                    "Import {} project{} with {} task{}?",
+                    projects.len(),
+                    if projects.len() == 1 { "" } else { "s" },
+                    task_count,
+                    if task_count == 1 { "" } else { "s" }
+                ));
+                ui.label(
+                    egui::RichText::new(
+                        "Replace discards the current projects. Merge adds these after them.",
+                    )
+                    .color(egui::Color32::GRAY),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Replace").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Merge").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if let Some(replace) = choice {
+            if let Some(projects) = self.pending_import.take() {
+                self.apply_import(projects, replace);
+            }
+        }
+        if close || !open {
+            self.pending_import = None;
+            self.import_error = None;
+        }
+    }
+
+    /// Brings imported projects in under fresh IDs so they can't collide with
+    /// existing projects, tasks or trash entries.
+    fn apply_import(&mut self, projects: Vec<Project>, replace: bool) {
+        if replace {
+            self.projects.clear();
+            self.new_task_texts.clear();
+            self.right_click_task_text.clear();
+            self.project_search.clear();
+            self.editing_project = None;
+            self.editing_task = None;
+            self.focused_project = None;
+            self.adding_task_to_project = None;
+            self.quick_add_project = None;
+        }
+
+        let now = Local::now().naive_local();
+        for mut project in projects {
+            project.id = self.next_project_id;
+            self.next_project_id += 1;
+
+            // Merging next to a same-named project gets a numbered suffix
+            let base_name = project.name.clone();
+            let mut suffix = 2;
+            while self.project_name_taken(&project.name, None) {
+                project.name = format!("{} ({})", base_name, suffix);
+                suffix += 1;
+            }
+
+            let mut new_ids = HashMap::new();
+            for task in &mut project.tasks {
+                new_ids.insert(task.id, self.next_task_id);
+                task.id = self.next_task_id;
+                self.next_task_id += 1;
+                // Same rule as at launch: no reminders for tasks already overdue
+                if task.due_at().is_some_and(|due| due <= now) {
+                    self.fired_reminders.insert(task.id);
+                }
+            }
+            // Point dependencies at the renumbered tasks
+            for task in &mut project.tasks {
+                task.blocked_by = task.blocked_by.and_then(|id| new_ids.get(&id).copied());
+            }
+            self.projects.push(project);
+        }
+        self.dirty = true;
+    }
+
+    fn tasks_to_csv(&self) -> String {
+        let mut csv = String::from("project,task,completed,due_date,due_time\n");
+        for project in &self.projects {
+            for task in &project.tasks {
+                let due_date = task.due_date.map(|d| d.to_string()).unwrap_or_default();
+                let due_time = task
+                    .due_time
+                    .map(|t| t.format("%H:%M").to_string())
+                    .unwrap_or_default();
+                csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(&project.name),
+                    csv_field(&task.text),
+                    task.completed,
+                    due_date,
+                    due_time
+                ));
+            }
+        }
+        csv
+    }
+
+    // Todo methods
+    fn find_task_mut(&mut self, project_id: usize, task_id: usize) -> Option<&mut Task> {
+        self.projects
+            .iter_mut()
+            .find(|p| p.id == project_id)?
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+    }
+
+    /// Remembers the current todo state so the next change can be undone.
+    fn push_undo(&mut self) {
+        self.undo_stack.push(UndoSnapshot {
+            projects: self.projects.clone(),
+            trash: self.trash.clone(),
+            next_project_id: self.next_project_id,
+            next_task_id: self.next_task_id,
+        });
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    fn undo(&mut self) {
+        let Some(snapshot) = self.undo_stack.pop() else {
+            return;
+        };
+        self.projects = snapshot.projects;
+        self.trash = snapshot.trash;
+        self.next_project_id = snapshot.next_project_id;
+        self.next_task_id = snapshot.next_task_id;
+        // Open editors may point at items the snapshot doesn't have
+        self.editing_project = None;
+        self.editing_task = None;
+        self.selected_task = None;
+        self.dirty = true;
+    }
+
+    fn remove_project(&mut self, project_id: usize) {
+        if let Some(index) = self.projects.iter().position(|p| p.id == project_id) {
+            let project = self.projects.remove(index);
+            self.move_to_trash(TrashItem::Project {
+                index,
+                project,
+                deleted_at: Some(Local::now()),
+            });
+        }
+        if self.focused_project == Some(project_id) {
+            self.focused_project = None;
+        }
+        self.new_task_texts.remove(&project_id);
+        self.right_click_task_text.remove(&project_id);
+        self.project_search.remove(&project_id);
+        self.detached_projects.remove(&project_id);
+        if self.adding_task_to_project == Some(project_id) {
+            self.adding_task_to_project = None;
+        }
+        if self.editing_project == Some(project_id) {
+            self.editing_project = None;
+        }
+        if self.paste_target == Some(project_id) {
+            self.paste_target = None;
+        }
+        if self.editing_task.is_some_and(|(p, _)| p == project_id) {
+            self.editing_task = None;
+        }
+        if self.selected_task.is_some_and(|(p, _)| p == project_id) {
+            self.selected_task = None;
+        }
+        self.dirty = true;
+    }
+
+    fn set_project_completed(&mut self, project_id: usize, completed: bool) {
+        if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+            // Leave already-matching tasks (and their timestamps) untouched
+            for task in project
+                .tasks
+                .iter_mut()
+                .filter(|t| t.completed != completed)
+            {
+                task.set_completed(completed);
+            }
+            self.dirty = true;
+        }
+    }
+
+    fn remove_task(&mut self, project_id: usize, task_id: usize) {
+        let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) else {
+            return;
+        };
+        if let Some(index) = project.tasks.iter().position(|t| t.id == task_id) {
+            let task = project.tasks.remove(index);
+            clear_blocker(&mut project.tasks, task_id);
+            let project_name = project.name.clone();
+            self.move_to_trash(TrashItem::Task {
+                project_id,
+                project_name,
+                index,
+                task,
+                deleted_at: Some(Local::now()),
+            });
+        }
+    }
+
+    fn move_to_trash(&mut self, item: TrashItem) {
+        self.trash.push(item);
+        // Drop the oldest entries beyond the cap
+        if self.trash.len() > TRASH_CAPACITY {
+            let overflow = self.trash.len() - TRASH_CAPACITY;
+            self.trash.drain(..overflow);
+        }
+        self.dirty = true;
+    }
+
+    /// Applies a new retention period, purging what is now past it.
+    fn set_trash_retention(&mut self, days: u32) {
+        if days != self.trash_retention_days {
+            self.trash_retention_days = days;
+            self.dirty = true;
+            self.purge_expired_trash(Local::now());
+        }
+    }
+
+    /// Drops trash entries older than the retention period (if there is one).
+    fn purge_expired_trash(&mut self, now: DateTime<Local>) {
+        if self.trash_retention_days == 0 {
+            return;
+        }
+        let cutoff = now - chrono::Duration::days(self.trash_retention_days as i64);
+        let before = self.trash.len();
+        self.trash
+            .retain(|item| item.deleted_at().is_none_or(|deleted| deleted > cutoff));
+        if self.trash.len() != before {
+            self.dirty = true;
+        }
+    }
 
-                    // Check if click is not on any text box
-                    let on_textbox = self.notes_canvas.text_boxes.iter().any(|tb| {
-                        egui::Rect::from_min_size(tb.position, tb.size).contains(canvas_pos)
+    fn restore_from_trash(&mut self, trash_idx: usize) {
+        if trash_idx >= self.trash.len() {
+            return;
+        }
+        match self.trash.remove(trash_idx) {
+            TrashItem::Task {
+                project_id,
+                project_name,
+                index,
+                task,
+                ..
+            } => {
+                // Recreate the original project if it is gone too
+                if !self.projects.iter().any(|p| p.id == project_id) {
+                    self.projects.push(Project {
+                        id: project_id,
+                        name: project_name,
+                        tasks: Vec::new(),
+                        expanded: true,
+                        wip_limit: None,
+                        pinned: false,
+                        sort: TaskSort::Manual,
+                        description: String::new(),
                     });
-
-                    if !on_textbox {
-                        self.context_menu_pos = Some(screen_pos);
-                    }
+                }
+                if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+                    let index = index.min(project.tasks.len());
+                    project.tasks.insert(index, task);
+                }
+            }
+            TrashItem::Project { index, project, .. } => {
+                if let Some(existing) = self.projects.iter_mut().find(|p| p.id == project.id) {
+                    // A restored task already recreated this project; merge into it
+                    existing.tasks.extend(project.tasks);
+                } else {
+                    let index = index.min(self.projects.len());
+                    self.projects.insert(index, project);
                 }
             }
-        });
-
-        // Show context menu
-        if let Some(menu_screen_pos) = self.context_menu_pos {
-            egui::Area::new(egui::Id::new("context_menu"))
-                .fixed_pos(menu_screen_pos)
-                .show(ui.ctx(), |ui| {
-                    egui::Frame::popup(ui.style()).show(ui, |ui| {
-                        if ui.button("Create Text Box").clicked() {
-                            let canvas_pos = menu_screen_pos - pan_offset;
-                            self.create_text_box_at(canvas_pos);
-                            self.context_menu_pos = None;
-                        }
-                        if ui.button("Cancel").clicked() {
-                            self.context_menu_pos = None;
-                        }
-                    });
-                });
         }
+        self.dirty = true;
+    }
 
-        // Close menu on click elsewhere
-        ui.input(|i| {
-            if i.pointer.primary_clicked() && self.context_menu_pos.is_some() {
-                self.context_menu_pos = None;
+    fn duplicate_task(&mut self, project_id: usize, task_id: usize) {
+        if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
+            if let Some(idx) = project.tasks.iter().position(|t| t.id == task_id) {
+                // The copy starts out as a fresh, incomplete task right below the original
+                let mut copy = project.tasks[idx].clone();
+                copy.id = self.next_task_id;
+                copy.created_at = Some(Local::now());
+                copy.set_completed(false);
+                project.tasks.insert(idx + 1, copy);
+                self.next_task_id += 1;
+                self.dirty = true;
             }
-        });
+        }
     }
 
-    fn create_text_box_at(&mut self, pos: egui::Pos2) {
-        let text_box = TextBox {
-            id: self.notes_canvas.next_textbox_id,
-            title: format!("Note {}", self.notes_canvas.next_textbox_id),
-            position: pos,
-            size: egui::vec2(400.0, 250.0), // Fixed default size
-            content: String::new(),
-            auto_height: false, // Start in manual mode as requested
-            min_size: egui::vec2(150.0, 80.0),
-            is_dragging: false,
+    fn move_task(&mut self, project_id: usize, task_id: usize, target_id: usize) {
+        if project_id == target_id || !self.projects.iter().any(|p| p.id == target_id) {
+            return;
+        }
+        let Some(mut task) = self
+            .projects
+            .iter_mut()
+            .find(|p| p.id == project_id)
+            .and_then(|p| {
+                let idx = p.tasks.iter().position(|t| t.id == task_id)?;
+                let task = p.tasks.remove(idx);
+                // Dependencies don't cross projects
+                clear_blocker(&mut p.tasks, task_id);
+                Some(task)
+            })
+        else {
+            return;
         };
+        task.blocked_by = None;
+        if let Some(target) = self.projects.iter_mut().find(|p| p.id == target_id) {
+            target.tasks.push(task);
+        }
+        if self.editing_task == Some((project_id, task_id)) {
+            self.editing_task = None;
+        }
+        self.dirty = true;
+    }
 
-        self.notes_canvas.text_boxes.push(text_box);
-        self.notes_canvas.next_textbox_id += 1;
+    /// Case-insensitive check against the other projects' (trimmed) names.
+    fn project_name_taken(&self, name: &str, except: Option<usize>) -> bool {
+        let name = name.trim().to_lowercase();
+        self.projects
+            .iter()
+            .filter(|p| Some(p.id) != except)
+            .any(|p| p.name.trim().to_lowercase() == name)
     }
 
-    // Todo methods
     fn add_project(&mut self) {
+        if self.project_name_taken(&self.new_project_name, None) {
+            self.project_name_error = Some("A project with this name already exists".into());
+            return;
+        }
         if !self.new_project_name.trim().is_empty() {
             let project = Project {
                 id: self.next_project_id,
                 name: self.new_project_name.clone(),
                 tasks: Vec::new(),
-                expanded: true,
+                expanded: self.new_projects_expanded,
+                wip_limit: None,
+                pinned: false,
+                sort: TaskSort::Manual,
+                description: String::new(),
             };
             self.projects.push(project);
             self.next_project_id += 1;
             self.new_project_name.clear();
+            self.project_name_error = None;
+            self.dirty = true;
         }
     }
 
-    fn add_task_to_project(&mut self, project_id: usize, task_text: String) {
-        if let Some(project) = self.projects.iter_mut().find(|p| p.id == project_id) {
-            if !task_text.trim().is_empty() {
-                let task = Task {
-                    id: self.next_task_id,
-                    text: task_text.trim().to_string(),
-                    completed: false,
-                };
-                project.tasks.push(task);
-                self.next_task_id += 1;
+    /// Returns the new task's ID, or `None` if nothing was added.
+    fn add_task_to_project(&mut self, project_id: usize, task_text: String) -> Option<usize> {
+        let project = self.projects.iter_mut().find(|p| p.id == project_id)?;
+        if task_text.trim().is_empty() {
+            return None;
+        }
+        let task = Task {
+            id: self.next_task_id,
+            text: task_text.trim().to_string(),
+            completed: false,
+            created_at: Some(Local::now()),
+            completed_at: None,
+            due_date: None,
+            due_time: None,
+            flagged: false,
+            snoozed: false,
+            blocked_by: None,
+            notes: String::new(),
+            expanded: false,
+            estimate_minutes: None,
+        };
+        project.tasks.push(task);
+        self.next_task_id += 1;
+        self.dirty = true;
+        Some(self.next_task_id - 1)
+    }
+}
+
+/// Byte ranges of every non-overlapping, case-insensitive occurrence of `query`.
+fn find_case_insensitive(text: &str, query: &str) -> Vec<std::ops::Range<usize>> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut ranges = Vec::new();
+    if query.is_empty() {
+        return ranges;
+    }
+
+    let mut start = 0;
+    while start < chars.len() {
+        // Compare lowercased chars one by one so multi-char lowercase forms line up
+        let (mut matched, mut end) = (0, start);
+        while matched < query.len() && end < chars.len() {
+            let lower: Vec<char> = chars[end].1.to_lowercase().collect();
+            if !query[matched..].starts_with(&lower) {
+                break;
+            }
+            matched += lower.len();
+            end += 1;
+        }
+
+        if matched == query.len() {
+            let (last_byte, last_char) = chars[end - 1];
+            ranges.push(chars[start].0..last_byte + last_char.len_utf8());
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+    ranges
+}
+
+/// Splits `segment` into `(range, is_highlighted)` runs using sorted highlight ranges.
+fn split_highlights(
+    segment: std::ops::Range<usize>,
+    highlights: &[std::ops::Range<usize>],
+) -> Vec<(std::ops::Range<usize>, bool)> {
+    let mut runs = Vec::new();
+    let mut cursor = segment.start;
+
+    for highlight in highlights {
+        let start = highlight.start.max(segment.start);
+        let end = highlight.end.min(segment.end);
+        if start >= end {
+            continue;
+        }
+        if cursor < start {
+            runs.push((cursor..start, false));
+        }
+        // Merge with a directly preceding highlighted run (e.g. consecutive fuzzy hits)
+        match runs.last_mut() {
+            Some((previous, true)) if previous.end == start => previous.end = end,
+            _ => runs.push((start..end, true)),
+        }
+        cursor = end;
+    }
+
+    if cursor < segment.end {
+        runs.push((cursor..segment.end, false));
+    }
+    runs
+}
+
+/// Changes whenever `value` does, so a lazily drawn row showing it gets re-measured.
+fn row_stamp(value: &impl Serialize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_vec(value)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// One task per non-blank line, as (text, completed). Markdown checklist
+/// items (`- [ ] text`, `- [x] text`) keep their checkbox state.
+fn parse_pasted_tasks(text: &str) -> Vec<(String, bool)> {
+    text.lines()
+        .map(str::trim)
+        .map(|line| match parse_checklist_item(line) {
+            Some((text, completed)) => (text.to_string(), completed),
+            None => (line.to_string(), false),
+        })
+        .filter(|(text, _)| !text.is_empty())
+        .collect()
+}
+
+/// Drops open tasks past the `slots` left under a WIP limit, returning the rest
+/// and how many were dropped. Completed tasks don't count towards the limit.
+fn fit_to_open_slots(
+    tasks: Vec<(String, bool)>,
+    slots: Option<usize>,
+) -> (Vec<(String, bool)>, usize) {
+    let Some(mut slots) = slots else {
+        return (tasks, 0);
+    };
+    let mut skipped = 0;
+    let kept = tasks
+        .into_iter()
+        .filter(|(_, completed)| {
+            if *completed {
+                true
+            } else if slots > 0 {
+                slots -= 1;
+                true
+            } else {
+                skipped += 1;
+                false
+            }
+        })
+        .collect();
+    (kept, skipped)
+}
+
+/// Splits a `- [ ] text` / `* [x] text` line into its text and checkbox state.
+/// Anything else (including malformed boxes like `-[ ]` or `- [?]`) is `None`.
+fn parse_checklist_item(line: &str) -> Option<(&str, bool)> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))?
+        .trim_start();
+    let (completed, rest) = if let Some(rest) = rest.strip_prefix("[ ]") {
+        (false, rest)
+    } else if let Some(rest) = rest
+        .strip_prefix("[x]")
+        .or_else(|| rest.strip_prefix("[X]"))
+    {
+        (true, rest)
+    } else {
+        return None;
+    };
+    // The box must be followed by a space (or end the line)
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((rest.trim(), completed))
+}
+
+/// Case-insensitive subsequence match: every (non-space) query character must
+/// appear in `text` in order. Returns a score (higher is better) and the char
+/// indices of the matched characters. Consecutive and word-start hits score more.
+fn fuzzy_match(query: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let mut query_chars = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    query_chars.peek()?;
+
+    let mut score = 0;
+    let mut matched = Vec::new();
+    let mut previous: Option<char> = None;
+
+    for (idx, c) in text.chars().enumerate() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            score += 1;
+            if matched.last().is_some_and(|&last| last + 1 == idx) {
+                score += 5; // Consecutive run
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 3; // Start of a word
             }
+            matched.push(idx);
+            query_chars.next();
+        }
+        previous = Some(c);
+    }
+
+    if query_chars.peek().is_some() {
+        return None;
+    }
+    // Prefer tighter matches
+    let span = matched.last().unwrap_or(&0) - matched.first().unwrap_or(&0);
+    Some((score - span as i32 / 4, matched))
+}
+
+/// Splits `text` into `(segment, is_url)` parts around http(s) links.
+/// A link runs until the next whitespace, minus trailing punctuation.
+fn split_urls(text: &str) -> Vec<(&str, bool)> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let after = &rest[start..];
+        let mut end = after.find(char::is_whitespace).unwrap_or(after.len());
+        end = after[..end]
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"'])
+            .len();
+
+        // A bare scheme ("https://") is not worth linking
+        if after[..end].ends_with("//") {
+            segments.push((&rest[..start + end], false));
+            rest = &rest[start + end..];
+            continue;
+        }
+
+        if start > 0 {
+            segments.push((&rest[..start], false));
         }
+        segments.push((&after[..end], true));
+        rest = &after[end..];
+    }
+
+    if !rest.is_empty() {
+        segments.push((rest, false));
+    }
+    segments
+}
+
+/// Drops every dependency on `task_id`, e.g. after it was deleted.
+fn clear_blocker(tasks: &mut [Task], task_id: usize) {
+    for task in tasks.iter_mut().filter(|t| t.blocked_by == Some(task_id)) {
+        task.blocked_by = None;
+    }
+}
+
+/// `schema_version` of saved data; 0 for saves made before versioning.
+fn saved_schema_version(value: &serde_json::Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .map_or(0, |v| u32::try_from(v).unwrap_or(u32::MAX))
+}
+
+/// Sort key for the due-date order: incomplete before completed, then by due
+/// date with undated tasks last. A date without a time comes after timed
+/// tasks on the same day.
+fn due_date_key(task: &Task) -> (bool, bool, Option<NaiveDate>, bool, Option<NaiveTime>) {
+    (
+        task.completed,
+        task.due_date.is_none(),
+        task.due_date,
+        task.due_time.is_none(),
+        task.due_time,
+    )
+}
+
+/// Small marker shown next to an edit field whose text differs from the original.
+fn unsaved_edit_marker(ui: &mut egui::Ui) {
+    ui.label(
+        egui::RichText::new(icons::icons::ICON_EDIT_NOTE)
+            .size(BUTTON_SIZE)
+            .color(egui::Color32::from_rgb(230, 150, 0)),
+    )
+    .on_hover_text("Unsaved changes");
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Relative countdown to a due date, colored by urgency: red once past,
+/// amber for today and tomorrow, gray further out.
+fn format_due(due: NaiveDate, today: NaiveDate) -> (String, egui::Color32) {
+    let days = (due - today).num_days();
+    let amber = egui::Color32::from_rgb(230, 150, 0);
+    match days {
+        ..=-2 => (format!("{} days ago", -days), egui::Color32::RED),
+        -1 => ("1 day ago".to_string(), egui::Color32::RED),
+        0 => ("due today".to_string(), amber),
+        1 => ("due tomorrow".to_string(), amber),
+        _ => (format!("in {} days", days), egui::Color32::GRAY),
+    }
+}
+
+/// A duration in minutes as "45m", "2h" or "2h 30m".
+fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// Formats how long ago `then` was, relative to `now` ("just now", "2h ago",
+/// "yesterday", ...). Falls back to the date for anything older than a week.
+fn format_time_since(then: DateTime<Local>, now: DateTime<Local>) -> String {
+    let delta = now.signed_duration_since(then);
+    let days_apart = (now.date_naive() - then.date_naive()).num_days();
+
+    if delta.num_minutes() < 1 {
+        "just now".to_string()
+    } else if delta.num_hours() < 1 {
+        format!("{}m ago", delta.num_minutes())
+    } else if days_apart == 0 {
+        format!("{}h ago", delta.num_hours())
+    } else if days_apart == 1 {
+        "yesterday".to_string()
+    } else if days_apart < 7 {
+        format!("{}d ago", days_apart)
+    } else {
+        then.format("%d/%m/%Y").to_string()
     }
 }
 
@@ -1221,7 +5761,7 @@ fn main() -> Result<(), eframe::Error> {
     };
 
     eframe::run_native(
-        "Todo App",
+        APP_NAME,
         options,
         Box::new(|cc| {
             // Initialize the material icons - this sets up the icon fonts
@@ -1232,3 +5772,507 @@ fn main() -> Result<(), eframe::Error> {
         }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_with_projects(names: &[&str]) -> (TodoApp, Vec<usize>) {
+        let mut app = TodoApp::default();
+        let ids = names
+            .iter()
+            .map(|name| {
+                app.new_project_name = name.to_string();
+                app.add_project();
+                app.projects.last().unwrap().id
+            })
+            .collect();
+        (app, ids)
+    }
+
+    fn task_ids(app: &TodoApp, project_id: usize) -> Vec<usize> {
+        let project = app.projects.iter().find(|p| p.id == project_id).unwrap();
+        project.tasks.iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn migrate_upgrades_unversioned_saves() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        app.add_task_to_project(ids[0], "task".into());
+        let mut value = serde_json::to_value(&app).unwrap();
+        value.as_object_mut().unwrap().remove("schema_version");
+        assert_eq!(saved_schema_version(&value), 0);
+
+        let migrated = TodoApp::migrate(value).unwrap();
+        assert_eq!(migrated.schema_version, SCHEMA_VERSION);
+        assert_eq!(migrated.projects[0].tasks[0].text, "task");
+    }
+
+    #[test]
+    fn migrate_leaves_newer_saves_at_their_version() {
+        let mut value = serde_json::to_value(TodoApp::default()).unwrap();
+        value["schema_version"] = (SCHEMA_VERSION + 1).into();
+        assert_eq!(saved_schema_version(&value), SCHEMA_VERSION + 1);
+        let app = TodoApp::migrate(value).unwrap();
+        assert_eq!(app.schema_version, SCHEMA_VERSION + 1);
+    }
+
+    #[test]
+    fn migrate_rejects_data_that_is_not_an_app() {
+        assert!(TodoApp::migrate(serde_json::json!([1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn storage_lock_survives_clear_all() {
+        let mut app = TodoApp {
+            storage_locked: true,
+            ..Default::default()
+        };
+        app.clear_all_data();
+        assert!(app.storage_locked);
+    }
+
+    #[test]
+    fn trash_retention_purges_only_older_entries() {
+        let (mut app, ids) = app_with_projects(&["A", "B", "C"]);
+        app.remove_project(ids[0]);
+        app.remove_project(ids[1]);
+        app.remove_project(ids[2]);
+        let now = Local::now();
+        for (item, days) in app.trash.iter_mut().zip([40, 10, 0]) {
+            if let TrashItem::Project { deleted_at, .. } = item {
+                *deleted_at = Some(now - chrono::Duration::days(days));
+            }
+        }
+
+        app.trash_retention_days = 0;
+        app.purge_expired_trash(now);
+        assert_eq!(app.trash.len(), 3);
+
+        app.trash_retention_days = 30;
+        app.purge_expired_trash(now);
+        assert_eq!(app.trash.len(), 2);
+
+        app.set_trash_retention(5);
+        assert_eq!(app.trash.len(), 1);
+    }
+
+    #[test]
+    fn add_project_clears_the_draft_name() {
+        let (app, ids) = app_with_projects(&["Home"]);
+        assert_eq!(app.projects.len(), 1);
+        assert_eq!(app.projects[0].id, ids[0]);
+        assert_eq!(app.projects[0].name, "Home");
+        assert!(app.new_project_name.is_empty());
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn add_project_rejects_blank_names() {
+        let (mut app, _) = app_with_projects(&[]);
+        app.new_project_name = "   ".into();
+        app.add_project();
+        assert!(app.projects.is_empty());
+    }
+
+    #[test]
+    fn add_project_rejects_duplicate_names() {
+        let (mut app, _) = app_with_projects(&["Home"]);
+        app.new_project_name = " home ".into();
+        app.add_project();
+        assert_eq!(app.projects.len(), 1);
+        assert!(app.project_name_error.is_some());
+        // The draft is kept so it can be fixed
+        assert_eq!(app.new_project_name, " home ");
+    }
+
+    #[test]
+    fn project_ids_are_never_reused() {
+        let (mut app, ids) = app_with_projects(&["A", "B"]);
+        app.remove_project(ids[1]);
+        app.new_project_name = "C".into();
+        app.add_project();
+        let new_id = app.projects.last().unwrap().id;
+        assert!(!ids.contains(&new_id));
+    }
+
+    #[test]
+    fn task_ids_are_unique_across_projects() {
+        let (mut app, ids) = app_with_projects(&["A", "B"]);
+        let first = app.add_task_to_project(ids[0], "one".into()).unwrap();
+        let second = app.add_task_to_project(ids[1], "two".into()).unwrap();
+        app.duplicate_task(ids[0], first);
+        let mut all: Vec<usize> = ids.iter().flat_map(|&id| task_ids(&app, id)).collect();
+        assert_eq!(all.len(), 3);
+        assert!(all.contains(&second));
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn add_task_rejects_blank_text_and_unknown_projects() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        assert_eq!(app.add_task_to_project(ids[0], "  ".into()), None);
+        assert_eq!(app.add_task_to_project(ids[0] + 1, "task".into()), None);
+        assert!(task_ids(&app, ids[0]).is_empty());
+
+        let id = app
+            .add_task_to_project(ids[0], "  trimmed ".into())
+            .unwrap();
+        let task = app.find_task_mut(ids[0], id).unwrap();
+        assert_eq!(task.text, "trimmed");
+        assert!(!task.completed);
+    }
+
+    #[test]
+    fn removing_a_project_moves_it_to_the_trash() {
+        let (mut app, ids) = app_with_projects(&["A", "B"]);
+        app.add_task_to_project(ids[0], "task".into());
+        app.remove_project(ids[0]);
+        assert_eq!(app.projects.len(), 1);
+        assert_eq!(app.trash.len(), 1);
+
+        app.restore_from_trash(0);
+        assert_eq!(app.projects[0].id, ids[0]);
+        assert_eq!(task_ids(&app, ids[0]).len(), 1);
+        assert!(app.trash.is_empty());
+    }
+
+    #[test]
+    fn removing_a_project_clears_its_transient_state() {
+        let (mut app, ids) = app_with_projects(&["A", "B"]);
+        let task_id = app.add_task_to_project(ids[0], "task".into()).unwrap();
+        app.apply_project_action("add_task", ids[0], String::new());
+        app.new_task_texts.insert(ids[0], "draft".into());
+        app.new_task_texts.insert(ids[1], "other draft".into());
+        app.project_search.insert(ids[0], "query".into());
+        app.detached_projects.insert(ids[0]);
+        app.focused_project = Some(ids[0]);
+        app.selected_task = Some((ids[0], task_id));
+        app.start_edit_project(ids[0], "A".into());
+
+        app.apply_project_action("delete", ids[0], String::new());
+
+        assert!(!app.new_task_texts.contains_key(&ids[0]));
+        assert!(app.new_task_texts.contains_key(&ids[1]));
+        assert!(!app.right_click_task_text.contains_key(&ids[0]));
+        assert!(!app.project_search.contains_key(&ids[0]));
+        assert!(!app.detached_projects.contains(&ids[0]));
+        assert_eq!(app.adding_task_to_project, None);
+        assert_eq!(app.focused_project, None);
+        assert_eq!(app.selected_task, None);
+        assert_eq!(app.editing_project, None);
+    }
+
+    #[test]
+    fn edit_project_renames_and_rejects_taken_names() {
+        let (mut app, ids) = app_with_projects(&["A", "B"]);
+        app.start_edit_project(ids[0], "A".into());
+        app.edit_project_text = "b".into();
+        app.save_edit_project(ids[0]);
+        assert_eq!(app.editing_project, Some(ids[0]));
+        assert!(app.edit_project_error.is_some());
+
+        app.edit_project_text = "Renamed".into();
+        app.save_edit_project(ids[0]);
+        assert_eq!(app.editing_project, None);
+        assert_eq!(app.projects[0].name, "Renamed");
+    }
+
+    #[test]
+    fn edit_project_keeps_the_old_name_when_blank() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        app.start_edit_project(ids[0], "A".into());
+        app.edit_project_text = "  ".into();
+        app.save_edit_project(ids[0]);
+        assert_eq!(app.projects[0].name, "A");
+        assert_eq!(app.editing_project, None);
+    }
+
+    #[test]
+    fn stop_edit_discards_the_rename() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        app.start_edit_project(ids[0], "A".into());
+        app.edit_project_text = "B".into();
+        app.apply_project_action("stop_edit", ids[0], String::new());
+        assert_eq!(app.editing_project, None);
+        assert_eq!(app.projects[0].name, "A");
+    }
+
+    #[test]
+    fn submit_new_task_closes_the_entry_unless_rapid() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        app.apply_project_action("add_task", ids[0], String::new());
+        app.right_click_task_text.insert(ids[0], "first".into());
+        app.submit_new_task(ids[0]);
+        assert_eq!(task_ids(&app, ids[0]).len(), 1);
+        assert_eq!(app.adding_task_to_project, None);
+
+        app.rapid_task_entry = true;
+        app.apply_project_action("add_task", ids[0], String::new());
+        app.right_click_task_text.insert(ids[0], "second".into());
+        app.submit_new_task(ids[0]);
+        assert_eq!(task_ids(&app, ids[0]).len(), 2);
+        assert_eq!(app.adding_task_to_project, Some(ids[0]));
+        assert_eq!(app.right_click_task_text[&ids[0]], "");
+    }
+
+    #[test]
+    fn submit_new_task_keeps_a_blank_entry_open() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        app.apply_project_action("add_task", ids[0], String::new());
+        app.submit_new_task(ids[0]);
+        assert!(task_ids(&app, ids[0]).is_empty());
+        assert_eq!(app.adding_task_to_project, Some(ids[0]));
+    }
+
+    #[test]
+    fn saving_a_task_only_rearms_its_reminder_when_the_due_time_changes() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        let id = app.add_task_to_project(ids[0], "task".into()).unwrap();
+        let due = Local::now().date_naive();
+        app.find_task_mut(ids[0], id).unwrap().due_date = Some(due);
+        app.fired_reminders.insert(id);
+
+        app.apply_task_action("start_edit", ids[0], id, "task".into());
+        app.edit_task_text = "renamed".into();
+        app.apply_task_action("save_edit", ids[0], id, String::new());
+        assert!(app.fired_reminders.contains(&id));
+
+        app.apply_task_action("start_edit", ids[0], id, "renamed".into());
+        app.edit_task_due_date = due.succ_opt();
+        app.apply_task_action("save_edit", ids[0], id, String::new());
+        assert!(!app.fired_reminders.contains(&id));
+    }
+
+    #[test]
+    fn blockers_cannot_form_a_loop() {
+        let (mut app, ids) = app_with_projects(&["P"]);
+        let a = app.add_task_to_project(ids[0], "a".into()).unwrap();
+        let b = app.add_task_to_project(ids[0], "b".into()).unwrap();
+        let c = app.add_task_to_project(ids[0], "c".into()).unwrap();
+        let d = app.add_task_to_project(ids[0], "d".into()).unwrap();
+        app.find_task_mut(ids[0], a).unwrap().blocked_by = Some(b);
+        app.find_task_mut(ids[0], b).unwrap().blocked_by = Some(c);
+
+        let project = &app.projects[0];
+        assert!(project.depends_on(a, c));
+        assert!(project.depends_on(c, c));
+        assert!(!project.depends_on(c, a));
+        assert!(!project.depends_on(d, c));
+
+        // C -> A would close a -> b -> c -> a; saving it is refused
+        app.apply_task_action("start_edit", ids[0], c, "c".into());
+        app.edit_task_blocked_by = Some(a);
+        app.apply_task_action("save_edit", ids[0], c, String::new());
+        assert_eq!(app.find_task_mut(ids[0], c).unwrap().blocked_by, None);
+
+        app.apply_task_action("start_edit", ids[0], c, "c".into());
+        app.edit_task_blocked_by = Some(d);
+        app.apply_task_action("save_edit", ids[0], c, String::new());
+        assert_eq!(app.find_task_mut(ids[0], c).unwrap().blocked_by, Some(d));
+    }
+
+    #[test]
+    fn move_task_drops_cross_project_blockers() {
+        let (mut app, ids) = app_with_projects(&["A", "B"]);
+        let blocker = app.add_task_to_project(ids[0], "blocker".into()).unwrap();
+        let blocked = app.add_task_to_project(ids[0], "blocked".into()).unwrap();
+        app.find_task_mut(ids[0], blocked).unwrap().blocked_by = Some(blocker);
+
+        app.move_task(ids[0], blocker, ids[1]);
+        assert_eq!(task_ids(&app, ids[0]), vec![blocked]);
+        assert_eq!(task_ids(&app, ids[1]), vec![blocker]);
+        assert_eq!(app.find_task_mut(ids[0], blocked).unwrap().blocked_by, None);
+
+        // Moving into the same or a missing project does nothing
+        app.move_task(ids[1], blocker, ids[1]);
+        app.move_task(ids[1], blocker, ids[1] + 100);
+        assert_eq!(task_ids(&app, ids[1]), vec![blocker]);
+    }
+
+    #[test]
+    fn pasted_tasks_stop_at_the_wip_limit() {
+        let tasks = parse_pasted_tasks("one\n- [x] done\ntwo\nthree");
+
+        let (kept, skipped) = fit_to_open_slots(tasks.clone(), None);
+        assert_eq!((kept.len(), skipped), (4, 0));
+
+        // Completed lines don't use a slot
+        let (kept, skipped) = fit_to_open_slots(tasks, Some(1));
+        let texts: Vec<&str> = kept.iter().map(|(text, _)| text.as_str()).collect();
+        assert_eq!(texts, ["one", "done"]);
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn remaining_estimate_skips_done_and_unestimated_tasks() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        for (text, estimate) in [("a", Some(90)), ("b", Some(45)), ("c", None)] {
+            let id = app.add_task_to_project(ids[0], text.into()).unwrap();
+            app.find_task_mut(ids[0], id).unwrap().estimate_minutes = estimate;
+        }
+        let first = task_ids(&app, ids[0])[0];
+        assert_eq!(app.projects[0].remaining_estimate(), 135);
+        app.find_task_mut(ids[0], first)
+            .unwrap()
+            .set_completed(true);
+        assert_eq!(app.projects[0].remaining_estimate(), 45);
+    }
+
+    #[test]
+    fn smart_order_sorts_for_display_only() {
+        let (mut app, ids) = app_with_projects(&["A"]);
+        let done = app.add_task_to_project(ids[0], "done".into()).unwrap();
+        let undated = app.add_task_to_project(ids[0], "undated".into()).unwrap();
+        let later = app.add_task_to_project(ids[0], "later".into()).unwrap();
+        let flagged = app.add_task_to_project(ids[0], "flagged".into()).unwrap();
+        let soon = app.add_task_to_project(ids[0], "soon".into()).unwrap();
+        let today = Local::now().date_naive();
+        app.find_task_mut(ids[0], done).unwrap().set_completed(true);
+        for (id, days) in [(later, 5), (flagged, 5), (soon, 1)] {
+            app.find_task_mut(ids[0], id).unwrap().due_date =
+                Some(today + chrono::Duration::days(days));
+        }
+        app.find_task_mut(ids[0], flagged).unwrap().flagged = true;
+        app.smart_order = true;
+
+        let shown = |app: &TodoApp| -> Vec<usize> {
+            app.visible_tasks(&app.projects[0])
+                .iter()
+                .map(|t| t.id)
+                .collect()
+        };
+        assert_eq!(shown(&app), vec![soon, flagged, later, undated, done]);
+        assert_eq!(
+            task_ids(&app, ids[0]),
+            vec![done, undated, later, flagged, soon]
+        );
+
+        // Filters apply before the ordering
+        app.status_filter = StatusFilter::Active;
+        assert_eq!(shown(&app), vec![soon, flagged, later, undated]);
+    }
+
+    #[test]
+    fn lazy_rows_skip_off_screen_rows_once_measured() {
+        let ctx = egui::Context::default();
+        let mut app = TodoApp::default();
+        let frame = |app: &mut TodoApp| -> Vec<usize> {
+            let mut drawn = Vec::new();
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    egui::vec2(400.0, 300.0),
+                )),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for row in 0..100 {
+                            app.lazy_row(ui, egui::Id::new(("row", row)), 0, false, |_, ui| {
+                                ui.label(format!("Row {}", row));
+                                drawn.push(row);
+                            });
+                        }
+                    });
+                });
+            });
+            drawn
+        };
+
+        // Everything is drawn once to be measured, then only what fits on screen
+        assert_eq!(frame(&mut app).len(), 100);
+        let drawn = frame(&mut app);
+        assert!(drawn.contains(&0));
+        assert!(!drawn.contains(&99));
+    }
+
+    #[test]
+    fn lazy_rows_remeasure_rows_changed_off_screen() {
+        let ctx = egui::Context::default();
+        let mut app = TodoApp::default();
+        let mut texts: Vec<String> = (0..100).map(|row| format!("Row {}", row)).collect();
+        let frame = |app: &mut TodoApp, texts: &[String]| -> Vec<usize> {
+            app.begin_lazy_rows();
+            let mut drawn = Vec::new();
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    egui::vec2(400.0, 300.0),
+                )),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for (row, text) in texts.iter().enumerate() {
+                            let key = egui::Id::new(("row", row));
+                            app.lazy_row(ui, key, row_stamp(text), false, |_, ui| {
+                                ui.label(text.as_str());
+                                drawn.push(row);
+                            });
+                        }
+                    });
+                });
+            });
+            drawn
+        };
+        let tallest = |app: &TodoApp| {
+            app.row_heights
+                .values()
+                .map(|&(_, height)| height)
+                .fold(0.0, f32::max)
+        };
+
+        frame(&mut app, &texts);
+        assert!(!frame(&mut app, &texts).contains(&99));
+        let one_line = tallest(&app);
+
+        // The placeholder for a changed row is not trusted; it gets drawn again
+        texts[99] = "Row 99\nnow\nspans\nfive\nlines".to_string();
+        assert!(frame(&mut app, &texts).contains(&99));
+        assert!(tallest(&app) > one_line * 3.0);
+        assert!(!frame(&mut app, &texts).contains(&99));
+
+        // Rows that are gone stop being remembered
+        texts.truncate(10);
+        frame(&mut app, &texts);
+        frame(&mut app, &texts);
+        assert_eq!(app.row_heights.len(), 10);
+    }
+
+    #[test]
+    fn format_minutes_uses_hours_when_it_can() {
+        assert_eq!(format_minutes(45), "45m");
+        assert_eq!(format_minutes(60), "1h");
+        assert_eq!(format_minutes(150), "2h 30m");
+    }
+
+    #[test]
+    fn format_due_labels_and_colors_each_boundary() {
+        let amber = egui::Color32::from_rgb(230, 150, 0);
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let due = |date| format_due(date, today);
+
+        assert_eq!(due(day(10)), ("due today".to_string(), amber));
+        assert_eq!(due(day(11)), ("due tomorrow".to_string(), amber));
+        assert_eq!(due(day(12)), ("in 2 days".to_string(), egui::Color32::GRAY));
+        assert_eq!(due(day(9)), ("1 day ago".to_string(), egui::Color32::RED));
+        assert_eq!(due(day(7)), ("3 days ago".to_string(), egui::Color32::RED));
+
+        let far = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        assert_eq!(due(far), ("in 365 days".to_string(), egui::Color32::GRAY));
+    }
+
+    #[test]
+    fn format_due_counts_across_a_year_end() {
+        let new_year = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let old_year = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        assert_eq!(format_due(new_year, old_year).0, "due tomorrow");
+        assert_eq!(format_due(old_year, new_year).0, "1 day ago");
+    }
+}